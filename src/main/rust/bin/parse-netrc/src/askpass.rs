@@ -0,0 +1,155 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Understanding the prompts that git and ssh pass to an "askpass" program
+//! (see `GIT_ASKPASS` in git(1) and `SSH_ASKPASS` in ssh(1)).
+//!
+//! The program is run with the prompt text as its only argument, and is
+//! expected to print the answer on stdout. The prompts recognized are:
+//!
+//! * git: `Username for 'URL': ` and `Password for 'URL': `, where the URL
+//!   has the user name (for a password prompt), the host and the port (if
+//!   any) of the remote.
+//!
+//! * ssh: `USER@HOST's password: ` and, for keyboard-interactive
+//!   authentication, `(USER@HOST) Password: `.
+//!
+//! Anything else (a key passphrase, a host key confirmation, etc.) is not
+//! recognized, so that a caller can fail at once instead of guessing.
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::askpass::{Prompt, Wanted};
+//!
+//! let prompt = Prompt::parse("Password for 'https://me@github.com': ").unwrap();
+//! assert_eq!(prompt.wanted, Wanted::Password);
+//! assert_eq!((prompt.url.host.as_str(), prompt.url.username.as_deref()), ("github.com", Some("me")));
+//! ```
+
+use crate::url::Url;
+
+/// What a prompt asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wanted {
+    Username,
+    Password,
+}
+
+/// A recognized askpass prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prompt {
+    /// What is asked for.
+    pub wanted: Wanted,
+
+    /// Whose credentials are asked for. For an ssh prompt, the scheme is
+    /// "ssh".
+    pub url: Url,
+}
+
+impl Prompt {
+    /// Parses the prompt text `prompt`, if it is one of those recognized
+    /// (see the module-level docs).
+    pub fn parse(prompt: &str) -> Option<Prompt> {
+        let prompt = prompt.trim_end();
+
+        for &(lead, wanted) in &[("Username for '", Wanted::Username), ("Password for '", Wanted::Password)] {
+            if let Some(desc) = between(prompt, lead, "':") {
+                let url = Url::parse(desc).ok()?;
+                return Some(Prompt { wanted, url });
+            }
+        }
+
+        let user_host = between(prompt, "", "'s password:")
+            .or_else(|| between(prompt, "(", ") Password:"))?;
+        let at = user_host.rfind('@')?;
+        let (user, host) = (&user_host[..at], &user_host[at + 1..]);
+        if user.is_empty() || host.is_empty() || host.contains(char::is_whitespace) {
+            return None;
+        }
+        let host = match host.contains(':') {
+            true  => format!("[{}]", host),  // an IPv6 address
+            false => host.to_string(),
+        };
+        let url = Url { scheme: "ssh".to_string(), username: Some(user.to_string()), host, port: None };
+        Some(Prompt { wanted: Wanted::Password, url })
+    }
+}
+
+// The part of 'text' between 'prefix' and 'suffix', if it has both.
+fn between<'a>(text: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+    if text.len() >= prefix.len() + suffix.len() && text.starts_with(prefix) && text.ends_with(suffix) {
+        Some(&text[prefix.len()..text.len() - suffix.len()])
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // What is wanted, and the host, user and port of the URL.
+    type Parsed<'a> = (Wanted, &'a str, Option<&'a str>, Option<u16>);
+
+    #[test]
+    fn parse_prompts() {
+        let cases: &[(&str, Option<Parsed>)] = &[
+            ("Username for 'https://github.com': ",          Some((Wanted::Username, "github.com", None, None))),
+            ("Password for 'https://me@github.com': ",       Some((Wanted::Password, "github.com", Some("me"), None))),
+            ("Password for 'https://a%40b@ghe.corp:8443': ", Some((Wanted::Password, "ghe.corp", Some("a@b"), Some(8443)))),
+            ("Username for 'https://github.com/o/r.git': ",  Some((Wanted::Username, "github.com", None, None))),
+            ("git@github.com's password: ",                  Some((Wanted::Password, "github.com", Some("git"), None))),
+            ("(me@host.example) Password: ",                 Some((Wanted::Password, "host.example", Some("me"), None))),
+            ("me@fe80::1's password: ",                      Some((Wanted::Password, "[fe80::1]", Some("me"), None))),
+            ("Enter passphrase for key '/home/me/.ssh/id_ed25519': ", None),
+            ("Are you sure you want to continue connecting (yes/no/[fingerprint])? ", None),
+            ("Username for '': ",                            None),
+            ("Password: ",                                   None),
+            ("'s password: ",                                None),
+            ("",                                             None),
+        ];
+        for (text, expected) in cases {
+            let parsed = Prompt::parse(text)
+                .map(|p| (p.wanted, p.url.host.clone(), p.url.username.clone(), p.url.port));
+            let expected = expected.map(|(w, h, u, p)| (w, h.to_string(), u.map(str::to_string), p));
+            assert_eq!(parsed, expected, "prompt: {:?}", text);
+        }
+    }
+
+    #[test]
+    fn prompts_find_records() {
+        use crate::credential;
+        use crate::lookup::NetrcSource;
+
+        let loaded = NetrcSource::reader("test", "\
+machine api.github.com login me password ghp_api
+default login anonymous password guest
+".as_bytes()).load().unwrap();
+        let login = |text: &str| {
+            let prompt = Prompt::parse(text).unwrap();
+            credential::find(&loaded, &prompt.url, false, |query| query).ok().map(|m| m.machine.login.clone())
+        };
+        assert_eq!(login("Password for 'https://me@github.com': "), Some("me".to_string()));
+        assert_eq!(login("Username for 'https://API.GitHub.com': "), Some("me".to_string()));
+        assert_eq!(login("Password for 'https://nothere.com': "), None);
+        assert_eq!(login("git@nothere.com's password: "), None);
+    }
+}
@@ -0,0 +1,302 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Parsing the queries of the `--batch` mode of **`parse-netrc`**, one per
+//! line: either "HOST [USER]", or a JSON object such as
+//! `{"host": "api.github.com", "user": "me"}`.
+//!
+//! Just enough JSON is understood for that: an object whose members are
+//! strings, numbers, booleans or null. The "host" member (a non-empty string)
+//! is required; the "user" member may be a string or null. Other members are
+//! ignored. As in most JSON parsers, a later member of the same name replaces
+//! an earlier one.
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::batch::{self, BatchQuery};
+//!
+//! let query = batch::parse_query(r#"{"host": "api.github.com", "user": "me", "id": 7}"#).unwrap();
+//! assert_eq!(query, BatchQuery { host: "api.github.com".to_string(), user: Some("me".to_string()) });
+//!
+//! assert_eq!(batch::parse_query("api.github.com").unwrap().user, None);
+//! assert!(batch::parse_query(r#"{"host": 42}"#).is_err());
+//! ```
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// One `--batch` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchQuery {
+    /// The host to look up.
+    pub host: String,
+
+    /// The user to look up, if any.
+    pub user: Option<String>,
+}
+
+/// Parses one query line (see the module-level docs): a JSON object, if it
+/// begins with `{`, and "HOST [USER]" otherwise. The error is a message that
+/// describes the problem.
+pub fn parse_query(line: &str) -> Result<BatchQuery, String> {
+    if line.trim_start().starts_with('{') {
+        return parse_json_query(line);
+    }
+
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words[..] {
+        [host]       => Ok(BatchQuery { host: host.to_string(), user: None }),
+        [host, user] => Ok(BatchQuery { host: host.to_string(), user: Some(user.to_string()) }),
+        _ => Err(format!("expected \"HOST [USER]\", but got {} words", words.len())),
+    }
+}
+
+/// Parses a JSON query object (see the module-level docs).
+pub fn parse_json_query(line: &str) -> Result<BatchQuery, String> {
+    let mut chars = line.trim().chars().peekable();
+
+    let mut host: Option<String> = None;
+    let mut user: Option<String> = None;
+
+    expect(&mut chars, '{')?;
+    skip_ws(&mut chars);
+    if Some(&'}') == chars.peek() {
+        chars.next();
+    } else {
+        loop {
+            expect(&mut chars, '"')?;
+            let key = string(&mut chars)?;
+            expect(&mut chars, ':')?;
+            skip_ws(&mut chars);
+            let value = match chars.peek() {
+                Some('"') => {
+                    chars.next();
+                    Some(string(&mut chars)?)
+                }
+                _ => {
+                    // A number, or 'true', 'false' or 'null'. Only 'null' is
+                    // acceptable for "host" and "user".
+                    let mut literal = String::new();
+                    while let Some(&ch) = chars.peek() {
+                        if !(ch.is_ascii_alphanumeric() || "+-.".contains(ch)) {
+                            break;
+                        }
+                        literal.push(ch);
+                        chars.next();
+                    }
+                    if "null" != literal && ("host" == key || "user" == key || literal.is_empty()) {
+                        return Err(format!("invalid JSON query: the value of \"{}\" must be a string or null", key));
+                    }
+                    None
+                }
+            };
+            match &key[..] {
+                "host" => host = value,
+                "user" => user = value,
+                _ => {}
+            }
+            skip_ws(&mut chars);
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err(format!("invalid JSON: expected ',' or '}}' after the value of \"{}\"", key)),
+            }
+        }
+    }
+    skip_ws(&mut chars);
+    if chars.next().is_some() {
+        return Err("invalid JSON: unexpected text after the object".to_string());
+    }
+
+    match host {
+        Some(host) if !host.is_empty() => Ok(BatchQuery { host, user }),
+        _ => Err("JSON query has no \"host\"".to_string()),
+    }
+}
+
+fn skip_ws(chars: &mut Peekable<Chars>) {
+    while chars.peek().map_or(false, |ch| ch.is_ascii_whitespace()) {
+        chars.next();
+    }
+}
+
+fn expect(chars: &mut Peekable<Chars>, want: char) -> Result<(), String> {
+    skip_ws(chars);
+    match chars.next() {
+        Some(ch) if ch == want => Ok(()),
+        Some(ch) => Err(format!("invalid JSON: expected '{}', but got '{}'", want, ch)),
+        None     => Err(format!("invalid JSON: expected '{}', but got the end of the line", want)),
+    }
+}
+
+fn hex4(chars: &mut Peekable<Chars>) -> Result<u32, String> {
+    let digits: String = chars.by_ref().take(4).collect();
+    match u32::from_str_radix(&digits, 16) {
+        Ok(val) if 4 == digits.len() => Ok(val),
+        _ => Err(format!("invalid JSON: bad \\u escape: \"{}\"", digits)),
+    }
+}
+
+// A JSON string (the opening quote already consumed).
+fn string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    let mut rtn = String::new();
+    loop {
+        match chars.next() {
+            None => return Err("invalid JSON: unterminated string".to_string()),
+            Some('"') => return Ok(rtn),
+            Some('\\') => {
+                let ch = match chars.next() {
+                    Some('"')  => '"',
+                    Some('\\') => '\\',
+                    Some('/')  => '/',
+                    Some('b')  => '\u{8}',
+                    Some('f')  => '\u{c}',
+                    Some('n')  => '\n',
+                    Some('r')  => '\r',
+                    Some('t')  => '\t',
+                    Some('u')  => {
+                        let mut code = hex4(chars)?;
+                        if (0xd800..0xdc00).contains(&code) {
+                            // A UTF-16 surrogate pair
+                            if !(Some('\\') == chars.next() && Some('u') == chars.next()) {
+                                return Err("invalid JSON: unpaired surrogate in \\u escape".to_string());
+                            }
+                            let low = hex4(chars)?;
+                            if !(0xdc00..0xe000).contains(&low) {
+                                return Err("invalid JSON: unpaired surrogate in \\u escape".to_string());
+                            }
+                            code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                        }
+                        std::char::from_u32(code)
+                            .ok_or_else(|| format!("invalid JSON: bad \\u escape: {:04x}", code))?
+                    }
+                    Some(ch) => return Err(format!("invalid JSON: bad escape: \\{}", ch)),
+                    None => return Err("invalid JSON: unterminated string".to_string()),
+                };
+                rtn.push(ch);
+            }
+            Some(ch) if (ch as u32) < 0x20 => return Err("invalid JSON: control character in string".to_string()),
+            Some(ch) => rtn.push(ch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A line, with the host and user it parses to (None if it is an error).
+    type Case<'a> = (&'a str, Option<(&'a str, Option<&'a str>)>);
+
+    fn check(cases: &[Case]) {
+        for &(line, expected) in cases {
+            let parsed = parse_query(line);
+            let expected = expected.map(|(host, user)| BatchQuery {
+                host: host.to_string(),
+                user: user.map(|u| u.to_string()),
+            });
+            assert_eq!(parsed.as_ref().ok(), expected.as_ref(), "line: {:?} => {:?}", line, parsed);
+        }
+    }
+
+    #[test]
+    fn text_queries() {
+        check(&[
+            ("api.github.com",          Some(("api.github.com", None))),
+            ("  api.github.com\tme ",   Some(("api.github.com", Some("me")))),
+            ("api.github.com me you",   None),
+            ("",                        None),
+        ]);
+    }
+
+    #[test]
+    fn json_queries() {
+        check(&[
+            (r#"{"host":"h"}"#,                             Some(("h", None))),
+            (r#" { "user" : "me" , "host" : "h" } "#,       Some(("h", Some("me")))),
+            (r#"{"host":"h","user":null}"#,                 Some(("h", None))),
+            (r#"{"host":"a\/b\\c\"d\te"}"#,                 Some(("a/b\\c\"d\te", None))),
+            (r#"{"host":"h","user":"j\u00f6rg"}"#,          Some(("h", Some("j\u{f6}rg")))),
+            (r#"{"host":"","user":"me"}"#,                  None),
+            (r#"{"host":null}"#,                            None),
+            (r#"{"user":"me"}"#,                            None),
+            (r#"{}"#,                                       None),
+        ]);
+    }
+
+    #[test]
+    fn json_duplicate_keys() {
+        // The last one wins, as in most JSON parsers.
+        check(&[
+            (r#"{"host":"a","host":"b"}"#,                  Some(("b", None))),
+            (r#"{"host":"h","user":"me","user":null}"#,     Some(("h", None))),
+            (r#"{"host":"h","user":null,"user":"me"}"#,     Some(("h", Some("me")))),
+            (r#"{"host":"h","host":null}"#,                 None),
+        ]);
+    }
+
+    #[test]
+    fn json_surrogate_pairs() {
+        check(&[
+            (r#"{"host":"h","user":"\ud83d\udd11"}"#,       Some(("h", Some("\u{1f511}")))),
+            (r#"{"host":"h","user":"\ud83d\udd11x"}"#,      Some(("h", Some("\u{1f511}x")))),
+            (r#"{"host":"h","user":"\ud83d"}"#,             None),
+            (r#"{"host":"h","user":"\ud83dx"}"#,            None),
+            (r#"{"host":"h","user":"\ud83d\u0041"}"#,       None),
+            (r#"{"host":"h","user":"\ud83d\ud83d"}"#,       None),
+            (r#"{"host":"h","user":"\udd11"}"#,             None),
+        ]);
+    }
+
+    #[test]
+    fn json_non_string_values() {
+        check(&[
+            (r#"{"host":"h","id":7,"ok":true,"no":false,"n":null,"f":-1.5e+3}"#, Some(("h", None))),
+            (r#"{"host":1}"#,                               None),
+            (r#"{"host":true}"#,                            None),
+            (r#"{"host":"h","user":false}"#,                None),
+            (r#"{"host":"h","user":0}"#,                    None),
+            (r#"{"host":"h","x":[1]}"#,                     None),
+            (r#"{"host":"h","x":{}}"#,                      None),
+            (r#"{"host":"h","x":}"#,                        None),
+        ]);
+    }
+
+    #[test]
+    fn json_malformed() {
+        check(&[
+            (r#"{"host":"h""#,                              None),
+            (r#"{"host":"h",}"#,                            None),
+            (r#"{"host" "h"}"#,                             None),
+            (r#"{"host":"h" "user":"me"}"#,                 None),
+            (r#"{host:"h"}"#,                               None),
+            (r#"{'host':'h'}"#,                             None),
+            (r#"{"host":"h"} x"#,                           None),
+            (r#"{"host":"h"}}"#,                            None),
+            (r#"{"host":"h\"}"#,                            None),
+            (r#"{"host":"h\x"}"#,                           None),
+            (r#"{"host":"h\u00"}"#,                         None),
+            (r#"{"host":"h\u00zz"}"#,                       None),
+            ("{\"host\":\"a\tb\"}",                         None),
+            ("{",                                           None),
+        ]);
+        assert!(parse_json_query(r#"["h"]"#).is_err());
+    }
+}
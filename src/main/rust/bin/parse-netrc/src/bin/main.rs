@@ -19,50 +19,62 @@
 
 //! parse-netrc: command line program to extract ~/.netrc bits
 //!
-//! The current behavior is limited to emitting just the username for the
-//! first matching netrc record found (because that was the itch the author
-//! needed to scratch). Could be adapted to extract the other record fields,
-//! if needed.
+//! By default, emits just the username for the first matching netrc record
+//! found (because that was the itch the author needed to scratch). Other
+//! record fields may be selected with the `--field` option.
 //!
-//! Limitations
-//! -----------
-//! The current implementation has a dependency on version 0.4.1 of the
-//! [`netrc`] library. This version of the library is not able to correctly
-//! parse all valid netrc files. The aspect of this most likely to affect most
-//! users is that it chokes on comments. It is the intent of the `parse-netrc`
-//! author to submit a patch to correct that behavior when time permits.
+//! Netrc parsing
+//! -------------
+//! The netrc parser is our in-tree copy of the third-party [`netrc`] library
+//! (version 0.4.1), available as the [`parse_netrc::netrc`] module. We carry
+//! it in-tree so that it can be made to accept the same netrc files that
+//! curl(1) accepts (for example, double-quoted tokens that contain
+//! whitespace).
 //!
 //! [netrc]:     https://crates.io/crates/netrc  "netrc (crates.io)"
-//! [netrc-doc]: http://yuhta.github.io/netrc-rs/doc/netrc/index.html
 //! [netrc-gh]:  https://github.com/Yuhta/netrc-rs
 
 use std::env;
 use std::error;
+use std::ffi::OsString;
 use std::fmt;
+use std::fs;
 use std::fs::File;
 use std::io;
+use std::io::BufRead;
 use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process;
 use std::string::String;
 use std::vec::Vec;
-
-// https://docs.rs/home/0.5.3/home/index.html
-// https://github.com/brson/home
-use home;
-
-// https://docs.rs/netrc/0.4.1/netrc/index.html
-// https://github.com/Yuhta/netrc-rs
-use netrc::Netrc;
+use std::borrow::Cow;
 
 // Our internal app-specific 'parse_netrc' library.
 //
-// XXX: Consider moving more of the application logic to the library to allow
-//      for easier unit- and integration testing.
+// The netrc file lookup logic lives in the library ('parse_netrc::lookup'), so
+// that other programs can use it, too; this program is a thin wrapper over it.
 use parse_netrc::{
+    askpass::{Prompt, Wanted},
+    batch::{self, BatchQuery},
     bld_date,     // bld_date!() macro
     bld_version,  // bld_version!() macro
     configure_time::MAINTAINER,
+    credential,
+    document::Document,
+    emit::Emit,
+    failure::Failure,
+    getopt::{self, HasArg, Item, OptSpec},
+    lint::{self, Severity},
+    decrypt::{self, Decrypt},
+    lookup::{self, Loaded, Match, NetrcSource, PermsCheck, Query},
+    password_cmd,
+    netrc,
+    quote::{json_quote, shell_quote},
+    secret::{self, Secret},
+    sources::{self, Credential, Resolver, Source},
+    url::{self, Url},
 };
 
 const PROG: &str = "parse-netrc";
@@ -75,7 +87,7 @@ static RELEASE: &str = concat!(bld_version!(), "  (built: ", bld_date!(), ")");
 // These two always emit their message:
 //
 macro_rules! pr_error   { ($($tts:tt)*) => { eprintln!("{} (error): {}",   PROG, format!($($tts)*)); } }
-// macro_rules! pr_warning { ($($tts:tt)*) => { eprintln!("{} (warning): {}", PROG, format!($($tts)*)); } }
+macro_rules! pr_warning { ($($tts:tt)*) => { eprintln!("{} (warning): {}", PROG, format!($($tts)*)); } }
 //
 // These three conditionally emit their message:
 //
@@ -116,14 +128,194 @@ mod GLOBAL {
     pub fn TRACING() -> bool         { unsafe { tracing_val } }
     #[allow(non_snake_case)]
     pub fn TRACING_set(newval: bool) { unsafe { tracing_val = newval /*copy*/ } }
+
+    #[allow(non_upper_case_globals)]
+    static mut porcelain_val: bool = false;
+
+    #[allow(non_snake_case)]
+    pub fn PORCELAIN() -> bool         { unsafe { porcelain_val } }
+    #[allow(non_snake_case)]
+    pub fn PORCELAIN_set(newval: bool) { unsafe { porcelain_val = newval /*copy*/ } }
 }
 use GLOBAL::*;
 
 
+// The fields of a netrc record that may be selected for output with the
+// '--field' option. The declaration order here determines the order in which
+// the fields are emitted, regardless of the order in which they were
+// requested on the command line.
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Field {
+    Machine,
+    Login,
+    Password,
+    Account,
+    Port,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Field> {
+        match name {
+            "machine"  => Some(Field::Machine),
+            "login"    => Some(Field::Login),
+            "password" => Some(Field::Password),
+            "account"  => Some(Field::Account),
+            "port"     => Some(Field::Port),
+            _          => None,
+        }
+    }
+}
+
+
+// The output formats that may be selected with the '-O' (--output-format)
+// option.
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+    Shell,
+    Nul,
+}
+
+impl OutputFormat {
+    fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "text"  => Some(OutputFormat::Text),
+            "json"  => Some(OutputFormat::Json),
+            "shell" => Some(OutputFormat::Shell),
+            "nul"   => Some(OutputFormat::Nul),
+            _       => None,
+        }
+    }
+}
+
+
+// The operations of the git credential helper protocol. See
+// gitcredentials(7).
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CredentialOp {
+    Get,
+    Store,
+    Erase,
+}
+
+impl CredentialOp {
+    fn from_name(name: &str) -> Option<CredentialOp> {
+        match name {
+            "get"   => Some(CredentialOp::Get),
+            "store" => Some(CredentialOp::Store),
+            "erase" => Some(CredentialOp::Erase),
+            _       => None,
+        }
+    }
+}
+
+
+// What the program has been asked to do.
+//
+#[derive(Debug)]
+enum Mode {
+
+    // Look up the netrc record for HOSTNAME (the default).
+    Lookup{ hostname: String },
+
+    // Look up the netrc record for the host and port of a URL ('--url=URL').
+    UrlLookup{ url: Url },
+
+    // Act as a git credential helper ('credential get|store|erase').
+    Credential( CredentialOp ),
+
+    // Answer a git or ssh prompt, as an askpass program ('askpass PROMPT').
+    Askpass{ prompt: String },
+
+    // List all of the netrc records ('--list').
+    List,
+
+    // Show the netrc records that could apply to HOSTNAME ('--show=HOST').
+    Show{ hostname: String },
+
+    // Answer the 'HOST [USER]' (or JSON) queries read from stdin ('--batch').
+    Batch,
+
+    // Check the given netrc files (or, if none were given, the netrc files
+    // that a lookup would use) for problems ('--lint [FILE...]').
+    Lint{ files: Vec<PathBuf> },
+
+    // List the names of the netrc 'macdef' macros ('--list-macros'), or print
+    // the body of one of them ('--macro=NAME').
+    ListMacros,
+    Macro{ name: String },
+
+    // Edit the netrc file in place: 'set HOST --login=LOGIN --password-stdin',
+    // 'remove HOST [--user=USER]' and 'rename-host OLD_HOST NEW_HOST'.
+    Set{ hostname: String, login: String },
+    Remove{ hostname: String },
+    RenameHost{ old_hostname: String, new_hostname: String },
+}
+
+
 #[derive(Debug)]
 struct Config {
-    hostname: String,
+    mode: Mode,
     username: Option<String>,
+
+    // Set by the '--account=NAME' and '--index=N' options, which select
+    // among several records for the same host (see host_query()).
+    account: Option<String>,
+    index: Option<usize>,
+
+    // Set by the '--all' option: print every matching record for the host,
+    // rather than just the one that is selected.
+    all: bool,
+
+    // Set by the '--sources=LIST' option: the credential sources to check, in
+    // order (see resolve_credential()). When None, just the netrc file(s).
+    sources: Option<Vec<Source>>,
+
+    // When true (the default), the netrc 'default' record (if any) is used
+    // when no 'machine' record matches, as curl(1) does. Disabled by the
+    // '--no-default' option.
+    use_default: bool,
+
+    // When true, the 'credential' and 'askpass' modes fall back to the netrc
+    // 'default' record, too (unless 'use_default' is false). Set by the
+    // '--helper-default' option.
+    helper_default: bool,
+
+    // The record fields to emit, sorted and without duplicates. Never empty;
+    // defaults to just the 'login' field.
+    fields: Vec<Field>,
+
+    // Set by the '--show-password' option. Needed to print the password
+    // field in any output format.
+    show_password: bool,
+
+    // Set by the '--allow-password-cmd' option. Needed to run the
+    // 'password_cmd' of a record (see with_password()).
+    allow_password_cmd: bool,
+
+    output_format: OutputFormat,
+
+    // Set by the '--emit=WORD' option, which replaces the output format (see
+    // print_record()).
+    emit: Option<Emit>,
+
+    // Set by the '--netrc-file' option. May name a single file or a
+    // colon-separated list of files.
+    netrc_files: Option<String>,
+
+    // Set by the '--decrypt-with=CMD' option: the command with which every
+    // netrc file is decrypted. Otherwise, only .gpg and .asc files are
+    // decrypted (see decrypt_setting()).
+    decrypt_with: Option<String>,
+
+    // When true (the default), a netrc file that is accessible by group or
+    // others, or that is not owned by the user, is an error. When false
+    // ('--no-strict-perms'), it elicits only a warning.
+    strict_perms: bool,
 }
 
 #[derive(Debug)]
@@ -134,7 +326,7 @@ enum CliSuccess {
 
     // The command line parameters were all successfully parsed, and used to
     // populate the contained `Config` struct. This will be the common case.
-    AdditionalProcessingRequired( Config ),
+    AdditionalProcessingRequired( Box<Config> ),  // boxed, as it is much larger than the other variant
 }
 
 
@@ -167,10 +359,36 @@ enum CliError {
     // location at which the error is being wrapped.
     IoErrorW(String, io::Error),
 
-    // Wraps any netrc::Error emitted by the underlying 'netrc' library. The
-    // string member is our app-specific error message, and the netrc::Error
-    // member is the wrapped error (the cause).
-    NetrcError(String, netrc::Error),
+    // The netrc file could not be parsed: a netrc::Error::Parse emitted by
+    // the underlying 'netrc' library, with the line number of the problem.
+    // (Its netrc::Error::Io errors become IoErrorW; see netrc_error().)
+    NetrcParseError{ path: PathBuf, lnum: usize, msg: String },
+
+    // None of the netrc files to be read exist (usually, just ~/.netrc).
+    NetrcFileNotFound{ paths: Vec<PathBuf> },
+
+    // An encrypted netrc file could not be decrypted. The string member
+    // describes the problem (never including any of the plaintext).
+    NetrcDecryptError{ path: PathBuf, msg: String },
+
+    // The netrc file exists, but is not a regular file.
+    NetrcNotAFile{ path: PathBuf },
+
+    // The user's home directory (where ~/.netrc lives) could not be
+    // determined (e.g., $HOME is not set, and the user has no passwd entry).
+    NoHomeDir,
+
+    // The netrc file is accessible by group or others, or is not owned by
+    // the user running the program. Curl (and ftp(1) before it) may refuse
+    // to use such a file. The string member describes the problem.
+    InsecureNetrcFile{ path: PathBuf, reason: String },
+
+    // '--lint' found problems in one or more netrc files. The diagnostics
+    // themselves have already been printed.
+    LintProblemsFound{ count: usize },
+
+    // No netrc 'macdef' macro has the name given with '--macro'.
+    NoMatchingMacro{ name: String },
 }
 
 
@@ -200,11 +418,35 @@ impl fmt::Display for CliError {
 
             // Produces messages like this:
             // <quote>
-            //     parse-netrc (error): Was unable to parse the user's netrc file: "/home/someuser/.netrc-BUSTED"
-            //         Wrapped netrc::Error: Parse("Unknown entry `this\'", 1)
+            //     parse-netrc (error): Was unable to parse the netrc file "/home/someuser/.netrc-BUSTED": line 1: Unknown entry `this'
             // </quote>
-            CliError::NetrcError(msg, err) =>
-                write!(ff, "{}\n    Wrapped netrc::Error: {:?}", msg, err ),
+            CliError::NetrcParseError{ path, lnum, msg } =>
+                write!(ff, "Was unable to parse the netrc file {:?}: line {}: {}", path, lnum, msg ),
+
+            CliError::NetrcFileNotFound{ paths } if paths.len() == 1 =>
+                write!(ff, "netrc file does not exist: {:?}", paths[0] ),
+
+            CliError::NetrcFileNotFound{ paths } =>
+                write!(ff, "none of the listed netrc files exist: {:?}", paths ),
+
+            CliError::NetrcNotAFile{ path } =>
+                write!(ff, "{:?} exists, but is not a file", path ),
+
+            CliError::NetrcDecryptError{ path, msg } =>
+                write!(ff, "Was unable to decrypt the netrc file {:?}: {}", path, msg ),
+
+            CliError::NoHomeDir =>
+                write!(ff, "was unable to determine the user's home directory (is $HOME set?)" ),
+
+            CliError::InsecureNetrcFile{ path, reason } =>
+                write!(ff, "netrc file {:?} is not safe to use: {}; curl(1) may refuse to use it",
+                       path, reason ),
+
+            CliError::NoMatchingMacro{ name } =>
+                write!(ff, "No netrc macro found with name: \"{}\"", &name),
+
+            CliError::LintProblemsFound{ count } =>
+                write!(ff, "{} problem(s) found in netrc file(s)", count ),
         }
     }
 }
@@ -215,6 +457,85 @@ impl From<io::Error> for CliError {
     }
 }
 
+impl CliError {
+
+    // The class of this error, which determines the exit status and the
+    // '--porcelain' error code (see the 'failure' module, and the "Exit
+    // status" section of print_help()).
+    //
+    fn failure( &self ) -> Failure {
+        match self {
+              CliError::NoMatchingNetrcRecord1{..}
+            | CliError::NoMatchingNetrcRecord2{..} => Failure::NoMatch,
+            CliError::NoMatchingMacro{..}          => Failure::NoMacro,
+            CliError::LintProblemsFound{..}        => Failure::LintProblems,
+            CliError::BadArgs(_)                   => Failure::Usage,
+            CliError::Msg(_)                       => Failure::Error,
+            CliError::InsecureNetrcFile{..}        => Failure::InsecurePerms,
+            CliError::NetrcFileNotFound{..}        => Failure::NetrcNotFound,
+            CliError::NetrcNotAFile{..}            => Failure::NetrcNotAFile,
+            CliError::NetrcParseError{..}          => Failure::NetrcParse,
+            CliError::NoHomeDir                    => Failure::NoHome,
+              CliError::IoError(_)
+            | CliError::IoErrorW(..)               => Failure::Io,
+            CliError::NetrcDecryptError{..}        => Failure::NetrcDecrypt,
+        }
+    }
+}
+
+// Converts an error from the 'netrc' library for the netrc file 'netrc_fpath'.
+//
+fn netrc_error( netrc_fpath: &Path, err: netrc::Error ) -> CliError {
+    match err {
+        netrc::Error::Parse(msg, lnum) =>
+            CliError::NetrcParseError{ path: netrc_fpath.to_path_buf(), lnum, msg },
+        netrc::Error::Io(err) =>
+            CliError::IoErrorW( format!( "Was unable to read the netrc file: {:?}", netrc_fpath ), err ),
+    }
+}
+
+impl From<sources::Error> for CliError {
+    fn from(err: sources::Error) -> CliError {
+        match err {
+            sources::Error::Netrc(err) =>
+                CliError::from( err ),
+            sources::Error::Io{ path, source } =>
+                CliError::IoErrorW( format!( "Was unable to read the credentials file: {:?}", path ), source ),
+            sources::Error::NoMatch{ host, user: None } =>
+                CliError::NoMatchingNetrcRecord1{ hostname: host },
+            sources::Error::NoMatch{ host, user: Some(user) } =>
+                CliError::NoMatchingNetrcRecord2{ hostname: host, username: user },
+        }
+    }
+}
+
+impl From<lookup::Error> for CliError {
+    fn from(err: lookup::Error) -> CliError {
+        match err {
+            lookup::Error::NoMatch{ host, user: None } =>
+                CliError::NoMatchingNetrcRecord1{ hostname: host },
+            lookup::Error::NoMatch{ host, user: Some(user) } =>
+                CliError::NoMatchingNetrcRecord2{ hostname: host, username: user },
+            lookup::Error::InsecureFile{ path, reason } =>
+                CliError::InsecureNetrcFile{ path, reason },
+            lookup::Error::Io{ path, source } =>
+                CliError::IoErrorW( format!( "Was unable to read the netrc file: {:?}", path ), source ),
+            lookup::Error::Parse{ path, source } =>
+                netrc_error( &path, source ),
+            lookup::Error::Decrypt{ path, source } =>
+                CliError::NetrcDecryptError{ path, msg: format!( "{}", source ) },
+            lookup::Error::NotFound(path) =>
+                CliError::NetrcFileNotFound{ paths: vec![ path ] },
+            lookup::Error::NoneFound(paths) =>
+                CliError::NetrcFileNotFound{ paths },
+            lookup::Error::NotAFile(path) =>
+                CliError::NetrcNotAFile{ path },
+            lookup::Error::NoHomeDir =>
+                CliError::NoHomeDir,
+        }
+    }
+}
+
 
 fn print_help<T: io::Write>(where_to: &mut T) -> Result<(), CliError> {
 
@@ -222,22 +543,283 @@ fn print_help<T: io::Write>(where_to: &mut T) -> Result<(), CliError> {
 r###"usage: {} {{ -h | --help }}
   or:  {} {{ -V | --version }}
   or:  {} [OPTION...] {{ -u USER | --user=USER }} [--] HOSTNAME
+  or:  {} [OPTION...] {{ -u USER | --user=USER }} --url=URL
+  or:  {} [OPTION...] {{ --account=NAME | --index=N | --all }} {{ HOSTNAME | --url=URL }}
+  or:  {} [OPTION...] --batch
+  or:  {} [OPTION...] credential {{ get | store | erase }}
+  or:  {} [OPTION...] askpass PROMPT
+  or:  {} [OPTION...] {{ --list | --show=HOST }}
+  or:  {} [OPTION...] {{ --list-macros | --macro=NAME }}
+  or:  {} [OPTION...] --lint [FILE...]
+  or:  {} [OPTION...] set HOST --login=LOGIN --password-stdin
+  or:  {} [OPTION...] remove HOST [--user=USER]
+  or:  {} [OPTION...] rename-host OLD_HOST NEW_HOST
 
 Extract and print fields from matching netrc record, if any.
 
+With --url, the host and port are taken from URL (a user name in URL is used
+as if given with --user). Host names are compared case-insensitively, and
+without regard to a trailing dot, the form of an IPv6 address, or whether an
+internationalized name is written in Unicode or in its "xn--" form. A record
+whose 'port' is the port of URL (given explicitly, or the default port of its
+scheme) is preferred; otherwise the first record with no 'port' is used.
+
+A netrc file may hold several records for the same host (e.g., work and
+personal GitHub tokens for "api.github.com"). A lookup selects the first of
+them, in file order, whose login is USER and whose 'account' field is NAME (of
+--account), if given. With --index=N, the Nth of them is selected instead
+(counting only 'machine' records); with --all, all of them are printed, one
+record per line, with the fields separated by tabs (with '-O json', as a JSON
+array). Note that curl(1), and so any API call made with 'curl --netrc' (as
+the ads-github-tools do), knows nothing of --account or --index: it always
+uses the first record for the host or, when the URL has a user name (as in
+'https://USER@api.github.com/'), the first record with that login. To have
+curl use another of the identities, put its login in the URL that way, or
+move its record ahead of the others.
+
+With --emit=WORD, the login and password of the matched record are printed
+as configuration for another program, to be read from a pipe, so that the
+program need not parse the netrc file itself (and nothing is written to
+disk). WORD is one of:
+
+    curl-config  a curl config file:  user = "LOGIN:PASSWORD"
+    auth-header  an HTTP header:      Authorization: token PASSWORD
+    wget-rc      a wgetrc file:       user = LOGIN / password = PASSWORD lines
+
+Values are quoted as the program expects; a value that it cannot represent
+(e.g., a login that contains a ':', for curl) is an error. For example:
+
+    {} --emit=curl-config --show-password api.github.com | curl -K - URL
+    {} --emit=auth-header --show-password api.github.com | curl -H @- URL
+    wget --config=<({} --emit=wget-rc --show-password HOST) URL
+
+With --sources=LIST, the credentials for HOSTNAME (or URL) may come from
+other places than the netrc file(s). LIST is a comma-separated list of these
+sources, which are checked in the order given:
+
+    netrc            the netrc file(s), as without --sources (a netrc file
+                       that does not exist is skipped)
+    env              $GH_TOKEN or $GITHUB_TOKEN, for github.com and
+                       api.github.com (or $GH_ENTERPRISE_TOKEN, for the
+                       $GH_HOST host); the login is USER or $GITHUB_ACTOR
+    gh               the hosts.yml file of gh(1), as written by 'gh auth login'
+    git-credentials  ~/.git-credentials (as written by 'git credential-store')
+
+The first source with credentials for the host wins; the credentials are
+printed as if they were a netrc record (the -v option tells which source
+supplied them, as do the "source" and "origin" members of the JSON object).
+Only a netrc record can match --account or --index. For example, in a CI job
+that provides GITHUB_TOKEN:
+
+    {} --sources=netrc,env -f login -f password --show-password api.github.com
+
+In place of its 'password', a record may give a 'password_cmd' (an extension
+to the netrc format), a command that prints the password:
+
+    machine api.github.com login me password_cmd "pass show github/token"
+
+With --allow-password-cmd, the command is run when (and only when) the
+password of the matched record is to be printed. It is not run by a shell
+(for a pipeline, use "sh -c '...'"), its stdin is /dev/null, it is killed if
+it has not finished within 30 seconds, and its output is trimmed of leading
+and trailing whitespace. Without --allow-password-cmd, a lookup that needs
+such a password fails; one that prints just the login never runs the command.
+
+A netrc file whose name ends in ".gpg" or ".asc" (such as the ~/.authinfo.gpg
+of Emacs users) is decrypted with "gpg --batch --quiet --decrypt" as it is
+read. With --decrypt-with=CMD, every netrc file is decrypted with CMD, which
+reads the file on its stdin and writes the plaintext on its stdout. Like a
+'password_cmd', CMD is not run by a shell, and is killed if it has not
+finished within 120 seconds. The plaintext is kept in memory only, and is
+never written to disk (so an encrypted file cannot be edited with 'set',
+'remove' or 'rename-host'). For example:
+
+    {} --netrc-file="$HOME/.authinfo.gpg" -f password --show-password HOST
+    {} --decrypt-with='age -d -i /path/to/key.txt' --netrc-file=.netrc.age HOST
+
+With --batch, read queries from stdin, one per line, each either "HOST [USER]"
+or a JSON object such as {{"host": "api.github.com", "user": "me"}}, and answer
+each on stdout, in order, after reading the netrc file(s) just once. Blank
+lines and lines that begin with '#' are skipped. Each answer is one line: in
+text format, a status ('found', 'not-found' or 'error') followed by the
+selected fields (or, for 'error', a message), separated by tabs; with '-O
+json', a JSON object with a "status" member. A query without a USER uses the
+--user value, if any. A query that finds nothing does not end the run, and the
+exit status is 0 unless the netrc file(s) cannot be read.
+
+With 'askpass', act as an askpass program for git (GIT_ASKPASS, see
+gitcredentials(7)) or ssh (SSH_ASKPASS): print the login or password asked
+for by PROMPT, which is one of git's "Username for 'URL': " or "Password for
+'https://USER@HOST': " prompts, or an ssh "USER@HOST's password: " prompt.
+The host, port and user are taken from the prompt, and the record is found
+as for 'credential' (see below): for "github.com", the "api.github.com"
+record is used if there is none for "github.com" itself, and the 'default'
+record is used only with --helper-default. Nothing is read from stdin, and any
+other prompt (e.g., for a key passphrase) fails at once, rather than leaving
+git or ssh waiting for an answer. Since git and ssh give the program nothing
+but the prompt, {} acts as 'askpass' when run by a name that ends in
+"-askpass". For example:
+
+    ln -s /path/to/{} ~/bin/{}-askpass
+    GIT_ASKPASS=~/bin/{}-askpass GIT_TERMINAL_PROMPT=0 git clone https://...
+
+With 'credential', act as a git credential helper (see gitcredentials(7)):
+read a request from stdin and, for 'get', answer with the username and
+password from the netrc 'machine' record that matches its 'host' and port
+(and 'username', if any), as for --url with the URL "PROTOCOL://HOST/". For
+"github.com", the "api.github.com" record is used if there is no record for
+"github.com" itself. The 'default' record is used only with --helper-default,
+so that its password is not handed to git for just any host. The netrc file
+is never modified; 'store' and 'erase' requests are ignored. For example:
+
+    git config --global credential.helper '/path/to/{} credential'
+
+With --lint, check each FILE (by default, the netrc files that a lookup would
+read) for problems, and report all of them, one per line, in the form
+"FILE:LINE:COLUMN: SEVERITY: MESSAGE". Errors are things that a netrc parser
+(or curl) would reject; warnings are things that parse, but are probably
+mistakes (a record shadowed by an earlier one, a 'default' record that is not
+last, a GitHub host with no token, etc.). The permissions and ownership of a
+netrc file are checked only when no FILE is named, so that the files in a
+dotfiles repository can be checked from a pre-commit hook.
+
+With 'set', 'remove' or 'rename-host', edit the netrc file in place. 'set'
+reads a password (e.g., a rotated GitHub token) from the first line of stdin
+and stores it in the record for HOST with the given login, adding the record
+(ahead of any 'default' record) if there is none. 'remove' deletes the records
+for HOST (just those for USER, if given), and 'rename-host' renames them.
+Comments, layout and all other records are left as they were. The file is
+replaced atomically with one of mode 0600, and the original is kept as
+FILE.bak. A symlink to the netrc file is followed (the link itself is kept).
+
 Mandatory arguments to long options are mandatory for short options too.
+Options may be given in any order, before or after HOSTNAME; a long option may
+be abbreviated to any unambiguous prefix of its name, and short options may be
+bundled ('-vvv', '-uUSER').
 
   -h, --help        Print this help message on stdout
   -V, --version     Print the version of the program on stdout
+      --account=NAME
+                    Require match of NAME in the 'account' field of the
+                      matched netrc record
+      --all         Print every netrc record that matches HOSTNAME (or URL),
+                      in file order, rather than just the first (see above).
+                      The shell output format is not supported
+      --allow-password-cmd
+                    Run the 'password_cmd' of the matched netrc record, when
+                      its password is needed (see above)
+      --batch       Answer many queries read from stdin (see above). Only the
+                      text and json output formats are supported
+      --decrypt-with=CMD
+                    Decrypt every netrc file with CMD as it is read, rather
+                      than only the .gpg and .asc files, with gpg (see above)
+      --emit=WORD   Print the login and password of the matched netrc record
+                      as curl-config, auth-header or wget-rc configuration
+                      (see above), rather than in an output format. Requires
+                      --show-password
+  -f, --field=NAME  Print the NAME field of the matched netrc record, one of:
+                      machine, login, password, account, or port. May be
+                      specified multiple times; the fields are printed one
+                      per line in the order listed here. The default is to
+                      print just the login field. The value printed for an
+                      absent field (or for 'machine' of the 'default'
+                      record) is an empty line
+      --helper-default
+                    In 'credential' and 'askpass' modes, fall back to the
+                      netrc 'default' record when no 'machine' record
+                      matches (see above)
+      --netrc-file=FILE
+                    Read the netrc file FILE rather than $NETRC or ~/.netrc.
+                      FILE may be a colon-separated list of files, which are
+                      searched in order (files that do not exist are skipped)
+      --lint        Check netrc files for problems (see above). Use with
+                      '-O json' for JSON output
+      --index=N     Select the Nth (counting from 1) of the 'machine' records
+                      that match HOSTNAME (or URL), -u USER and --account, in
+                      file order, rather than the first. The 'default' record
+                      is never selected
+      --list        List all of the netrc records, in order, with the file
+                      and line number of each, and whether a lookup of its
+                      host would select it. Passwords are redacted. Use with
+                      '-O json' for JSON output
+      --list-macros List the names of the netrc 'macdef' macros, one per line
+      --macro=NAME  Print the body of the netrc 'macdef' macro NAME verbatim:
+                      the lines that follow the 'macdef' line, up to (but not
+                      including) the blank line that ends the macro
+      --no-default  Do not fall back to the netrc 'default' record when no
+                      'machine' record matches HOSTNAME (strict host matching)
+      --no-strict-perms
+                    Only warn about a netrc file with unsafe permissions or
+                      ownership (see --strict-perms), and then use it anyway
+  -O, --output-format=WORD
+                    Emit output in the format specified by WORD [default: text]
+                      Valid values for WORD include:
+                        text:  one field per line
+                        json:  the full matched record as a single JSON object
+                               (password redacted unless --show-password)
+                        shell: NETRC_LOGIN='...' style assignments, safely
+                               quoted for use with 'eval'
+                        nul:   each field terminated by a NUL byte ('xargs -0')
+      --porcelain   On failure, print a stable error code and a message on
+                      stderr, as a single line of the form "CODE<TAB>MESSAGE"
+                      (see "Exit status", below), and nothing else (the help
+                      message is not printed for a usage error). It is the
+                      last line written to stderr
+      --show=HOST   Like --list, but show just the netrc records that could
+                      apply to HOST (including any 'default' record)
+      --sources=LIST
+                    Check the credential sources in LIST, in order, rather
+                      than just the netrc file(s) (see above). Sources: netrc,
+                      env, gh, git-credentials
+      --show-password
+                    Permit the password field to be printed. Without this
+                      option, '--field=password' is rejected, so that the
+                      password is never emitted by accident
+      --strict-perms
+                    Refuse to use a netrc file that is accessible by group or
+                      others, or that is not owned by the user (the default)
+      --url=URL     Look up the netrc record for the host and port of URL (see
+                      above), rather than for a HOSTNAME
   -u, --user=USER   Require match of USER in matched netrc record
   -v, --verbose     Print program progress messages on stderr. Specify multiple
                       times to increase verbosity: info, debug, and tracing
       --            Signals the end of options and disables further options processing.
                       Any remaining argument(s) will be interpretted as a hostname
+                      (even one named like a subcommand, e.g. 'credential')
+
+Environment:
+
+  NETRC             The netrc file (or colon-separated list of files) to read
+                      when the --netrc-file option is not specified. If unset
+                      or empty, $HOME/.netrc is read
+
+  POSIXLY_CORRECT   If set, options are recognized only ahead of the first
+                      non-option argument
+
+Exit status (and the --porcelain error code):
+
+  0                 A matching netrc record was found (--lint: no problems were found)
+  1                 No matching netrc record was found ('no-match'), there is no
+                      macro NAME ('no-macro'), or --lint found problems
+                      ('lint-problems')
+  2                 Invalid command line arguments ('usage'), or an error not
+                      listed here ('error')
+  3                 A netrc file has unsafe permissions or ownership (see
+                      --strict-perms) ('insecure-perms')
+  4                 The netrc file (or every one of a list of them) does not
+                      exist ('netrc-not-found')
+  5                 The netrc file is not a regular file ('netrc-not-a-file')
+  6                 The netrc file could not be parsed ('netrc-parse'); the
+                      message gives the line number
+  7                 The user's home directory could not be determined
+                      ('no-home')
+  8                 An I/O error occurred ('io')
+  9                 An encrypted netrc file could not be decrypted
+                      ('netrc-decrypt')
 
 Report bugs to {}.
 "###,
-              PROG, PROG, PROG, MAINTAINER )?;
+              PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, PROG, MAINTAINER )?;
 
     Ok(())
 }
@@ -273,8 +855,8 @@ fn fmt_netrc_machine(machine: &netrc::Machine) -> String {
     rtn.push_str( &(machine.login)[..] );
 
     rtn.push_str(", password: ");
-    if let Some(_) = &machine.password {
-        rtn.push_str("******");
+    if machine.password.is_some() || machine.password_cmd.is_some() {
+        rtn.push_str( secret::REDACTED );
     }
     else {
         rtn.push_str("[None]");
@@ -299,14 +881,87 @@ fn fmt_netrc_machine(machine: &netrc::Machine) -> String {
 
     rtn.push_str(" }");
 
-    return rtn;
+    rtn
+}
+
+
+// The options accepted before any subcommand (see print_help()).
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Opt {
+    Help,
+    Version,
+    Account,
+    All,
+    AllowPasswordCmd,
+    Batch,
+    DecryptWith,
+    Emit,
+    Field,
+    HelperDefault,
+    OutputFormat,
+    NetrcFile,
+    List,
+    Lint,
+    Url,
+    ListMacros,
+    Macro,
+    Show,
+    StrictPerms,
+    NoStrictPerms,
+    ShowPassword,
+    Sources,
+    NoDefault,
+    Index,
+    Porcelain,
+    User,
+    Verbose,
 }
 
+const OPTIONS: &[OptSpec<Opt>] = &[
+    OptSpec::new( Opt::Help,          Some('h'), Some("help"),            HasArg::No ),
+    OptSpec::new( Opt::Version,       Some('V'), Some("version"),         HasArg::No ),
+    OptSpec::new( Opt::Account,       None,      Some("account"),         HasArg::Required ),
+    OptSpec::new( Opt::All,           None,      Some("all"),             HasArg::No ),
+    OptSpec::new( Opt::AllowPasswordCmd, None,   Some("allow-password-cmd"), HasArg::No ),
+    OptSpec::new( Opt::Batch,         None,      Some("batch"),           HasArg::No ),
+    OptSpec::new( Opt::DecryptWith,   None,      Some("decrypt-with"),    HasArg::Required ),
+    OptSpec::new( Opt::Emit,          None,      Some("emit"),            HasArg::Required ),
+    OptSpec::new( Opt::Field,         Some('f'), Some("field"),           HasArg::Required ),
+    OptSpec::new( Opt::HelperDefault, None,      Some("helper-default"),  HasArg::No ),
+    OptSpec::new( Opt::OutputFormat,  Some('O'), Some("output-format"),   HasArg::Required ),
+    OptSpec::new( Opt::NetrcFile,     None,      Some("netrc-file"),      HasArg::Required ),
+    OptSpec::new( Opt::List,          None,      Some("list"),            HasArg::No ),
+    OptSpec::new( Opt::Lint,          None,      Some("lint"),            HasArg::No ),
+    OptSpec::new( Opt::Url,           None,      Some("url"),             HasArg::Required ),
+    OptSpec::new( Opt::ListMacros,    None,      Some("list-macros"),     HasArg::No ),
+    OptSpec::new( Opt::Macro,         None,      Some("macro"),           HasArg::Required ),
+    OptSpec::new( Opt::Show,          None,      Some("show"),            HasArg::Required ),
+    OptSpec::new( Opt::StrictPerms,   None,      Some("strict-perms"),    HasArg::No ),
+    OptSpec::new( Opt::NoStrictPerms, None,      Some("no-strict-perms"), HasArg::No ),
+    OptSpec::new( Opt::ShowPassword,  None,      Some("show-password"),   HasArg::No ),
+    OptSpec::new( Opt::Sources,       None,      Some("sources"),         HasArg::Required ),
+    OptSpec::new( Opt::NoDefault,     None,      Some("no-default"),      HasArg::No ),
+    OptSpec::new( Opt::Index,         None,      Some("index"),           HasArg::Required ),
+    OptSpec::new( Opt::Porcelain,     None,      Some("porcelain"),       HasArg::No ),
+    OptSpec::new( Opt::User,          Some('u'), Some("user"),            HasArg::Required ),
+    OptSpec::new( Opt::Verbose,       Some('v'), Some("verbose"),         HasArg::No ),
+];
+
+// The subcommands, each of which ends the global options (when it is the
+// first operand); the arguments that follow it are its own.
+//
+const SUBCOMMANDS: &[&str] = &["credential", "askpass", "set", "remove", "rename-host"];
 
 // Parses the provided arguments (presumably the command line arguments
 // provided to the program), sanity checks the values, and sets the
 // corresponding [`GLOBAL`] flags/values as appropriate.
 //
+// Options are parsed as GNU getopt_long(3) would (see the 'getopt' module):
+// '--user=USER' or '--user USER', '-uUSER' or '-u USER', bundled short options
+// ('-vvv'), unambiguous abbreviations of long options, and options anywhere on
+// the command line (unless POSIXLY_CORRECT is set), up to a "--" argument.
+//
 // Some command line options (such as '--help' and '--verbose') are handled as
 // soon as they are observed. No further processing is done beyond that point,
 // and the CliSuccess instance in our returned result will contain the
@@ -317,72 +972,238 @@ fn fmt_netrc_machine(machine: &netrc::Machine) -> String {
 // will contain the AdditionalProcessingRequired( Config ) variant, with the
 // parsed values contained therein.
 //
-fn parse_cli_args( args: &Vec<String>) -> Result<CliSuccess, CliError> {
+fn parse_cli_args( args: &[String] ) -> Result<CliSuccess, CliError> {
 
     pr_trace!("entered: parse_cli_args()");
     pr_trace!("parse_cli_args(): args.len() is: {}",  args.len());
 
     if args.len() < 2 {
-        return Err( CliError::BadArgs( format!("required HOSTNAME parameter not provided; bailing out" )));
+        return Err( CliError::BadArgs( "required HOSTNAME parameter not provided; bailing out".to_string() ));
     }
 
-    let input_hostname: String;
+    // The non-option arguments, in order.
+    let mut operands: Vec<String> = Vec::new();
 
-    let mut input_username = String::new();  // avoid E0381
-    let mut have_username = false;
+    // Set when the "--" pseudo opt is seen ahead of all of the operands, in
+    // which case the first operand is never treated as a subcommand name.
+    let mut saw_end_of_opts = false;
 
-    let mut skip_next_val = false;
+    let mut input_username: Option<String> = None;
 
-    // The index into args of the "current" item being examined. At the end of
-    // our args.iter() loop below, it will hold the index of the last
-    // successfully handled command line parameter.
-    //
-    let mut index: i32 = -1;
+    let mut account: Option<String> = None;
+    let mut index: Option<usize> = None;
+    let mut all = false;
 
-    pr_trace!("index before: {}", index);
+    let mut sources: Option<Vec<Source>> = None;
 
-    // avoid .skip(1) -- we're tracking the real index pos
-    for (idx, one_opt) in args.iter().enumerate() {
+    let mut use_default = true;
+    let mut helper_default = false;
 
-        // We're about to do a potentially unsafe cast from usize to i32, so
-        // make sure the value will fit.
-        if idx > ( i32::MAX as usize ) {
-            return Err( CliError::BadArgs( format!( "maxium number of arguments ({}) exceeded; bailing out", i32::MAX )));
-        }
+    let mut fields: Vec<Field> = Vec::new();
+    let mut show_password = false;
+    let mut allow_password_cmd = false;
 
-        index = idx as i32;  // copy (from usize)
+    let mut output_format = OutputFormat::Text;
+    let mut output_format_given = false;
 
-        pr_trace!("command line args[{}]: {}", idx, one_opt);  // may or may not be an option
+    let mut emit: Option<Emit> = None;
 
-        if 0 == idx { continue; }  // program name
+    let mut netrc_files: Option<String> = None;
+    let mut decrypt_with: Option<String> = None;
 
-        if skip_next_val {
-            pr_trace!("args[{}] already processed as an option argument; skipping", idx);
-            skip_next_val = false;
-            continue;
-        }
+    let mut strict_perms = true;
+
+    // Set by '--list' and '--show=HOST', respectively. These modes take no
+    // HOSTNAME argument.
+    let mut list_mode = false;
+    let mut show_hostname: Option<String> = None;
+
+    // Set by '--batch'. This mode takes no HOSTNAME argument.
+    let mut batch_mode = false;
+
+    // Set by '--lint'. The operands are the files to check.
+    let mut lint_mode = false;
+
+    // Set by '--url=URL'. This mode takes no HOSTNAME argument.
+    let mut input_url: Option<String> = None;
+
+    // Set by '--list-macros' and '--macro=NAME', respectively.
+    let mut list_macros_mode = false;
+    let mut macro_name: Option<String> = None;
+
+    let parser = getopt::Parser::new( &args[1..], OPTIONS )  // skip the program name
+        .posixly_correct( env::var_os("POSIXLY_CORRECT").is_some() )
+        .stop_at(|operand| SUBCOMMANDS.contains( &operand ));
+
+    for item in parser {
+
+        let item = item.map_err(|err| CliError::BadArgs( format!( "{}; bailing out", err )))?;
+        pr_trace!("command line item: {:?}", item);
+
+        let (opt, opt_name, optarg) = match item {
+            Item::Operand(operand) => {
+                operands.push( operand );
+                continue;
+            },
+            Item::EndOfOpts => {
+                pr_trace!("pseudo opt \"--\" found; any remaining args are operands");
+                saw_end_of_opts = operands.is_empty();
+                continue;
+            },
+            // The parser guarantees the presence of the 'value' of an option
+            // that requires one; 'optarg' is empty only for the others.
+            Item::Opt{ id, name, value } => (id, name, value.unwrap_or_default()),
+        };
+
+        match opt {
+
+            Opt::Help    => { print_help(    &mut io::stdout() )?; return Ok(CliSuccess::ProcessingIsComplete); },
+            Opt::Version => { print_version( &mut io::stdout() )?; return Ok(CliSuccess::ProcessingIsComplete); },
+
+            Opt::Field => {
+                let field = Field::from_name( &optarg )
+                    .ok_or_else(|| CliError::BadArgs( format!( "unrecognized field name for option {}: \"{}\"", opt_name, optarg )))?;
+                pr_trace!("have field from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+                fields.push( field );
+            },
+
+            Opt::OutputFormat => {
+                // If specified multiple times, the last one wins.
+                output_format = OutputFormat::from_name( &optarg )
+                    .ok_or_else(|| CliError::BadArgs( format!( "unrecognized output format for option {}: \"{}\"", opt_name, optarg )))?;
+                output_format_given = true;
+                pr_trace!("have output format from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+            },
+
+            Opt::Emit => {
+                // If specified multiple times, the last one wins.
+                emit = Some( Emit::from_name( &optarg )
+                    .ok_or_else(|| CliError::BadArgs( format!( "unrecognized format for option {}: \"{}\"", opt_name, optarg )))? );
+                pr_trace!("have emit format from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+            },
+
+            Opt::NetrcFile => {
+                // If specified multiple times, the last one wins.
+                pr_trace!("have netrc file(s) from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+                netrc_files = Some( optarg );
+            },
+
+            Opt::DecryptWith => {
+                // If specified multiple times, the last one wins.
+                pr_trace!("have decryption command from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+                decrypt_with = Some( optarg );
+            },
+
+            Opt::List => {
+                pr_trace!("will list all netrc records");
+                list_mode = true;
+            },
+
+            Opt::Batch => {
+                pr_trace!("will answer queries read from stdin");
+                batch_mode = true;
+            },
+
+            Opt::Lint => {
+                pr_trace!("will check netrc files for problems");
+                lint_mode = true;
+            },
+
+            Opt::Url => {
+                pr_trace!("have URL from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+                input_url = Some( optarg );
+            },
+
+            Opt::ListMacros => {
+                pr_trace!("will list the netrc macros");
+                list_macros_mode = true;
+            },
+
+            Opt::Macro => {
+                pr_trace!("have macro name from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+                macro_name = Some( optarg );
+            },
+
+            Opt::Show => {
+                pr_trace!("have show hostname from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+                show_hostname = Some( optarg );
+            },
+
+            Opt::StrictPerms => {
+                pr_trace!("will refuse netrc files with unsafe permissions");
+                strict_perms = true;
+            },
+
+            Opt::NoStrictPerms => {
+                pr_trace!("will only warn about netrc files with unsafe permissions");
+                strict_perms = false;
+            },
+
+            Opt::ShowPassword => {
+                pr_trace!("printing of the password field permitted");
+                show_password = true;
+            },
+
+            Opt::AllowPasswordCmd => {
+                pr_trace!("running of 'password_cmd' commands permitted");
+                allow_password_cmd = true;
+            },
+
+            Opt::NoDefault => {
+                pr_trace!("will not fall back to the netrc 'default' record");
+                use_default = false;
+            },
+
+            Opt::HelperDefault => {
+                pr_trace!("will fall back to the netrc 'default' record for git, too");
+                helper_default = true;
+            },
 
-        match &one_opt[..] {
+            Opt::Porcelain => {
+                // Usually already set by main(), ahead of any error in the
+                // options that precede it.
+                PORCELAIN_set( true );
+            },
 
-            "-h" | "--help"    => { print_help(    &mut io::stdout() )?; return Ok(CliSuccess::ProcessingIsComplete); },
-            "-V" | "--version" => { print_version( &mut io::stdout() )?; return Ok(CliSuccess::ProcessingIsComplete); },
+            Opt::User => {
+                if input_username.is_some() {
+                    return Err( CliError::BadArgs( "At most one -u (--user=USER) opt may be provided".to_string() ));
+                }
+                pr_trace!("have username from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+                input_username = Some( optarg );
+            },
 
-            "-u" | "--user"    => {
-                if have_username {
-                    return Err( CliError::BadArgs( format!( "At most one -u (--user=USER) opt may be provided" )));
+            Opt::Account => {
+                if account.is_some() {
+                    return Err( CliError::BadArgs( "At most one --account=NAME opt may be provided".to_string() ));
                 }
-                input_username = match args.get( idx + 1 ) {
-                    None => return Err( CliError::BadArgs( format!( "missing argument for option {}", one_opt ))),
-                    Some(optarg) => {
-                        pr_trace!("have username from opt: \"{}\", optarg: \"{}\"", one_opt, optarg);
-                        optarg.clone()
-                    }
+                pr_trace!("have account from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+                account = Some( optarg );
+            },
+
+            Opt::Index => {
+                // If specified multiple times, the last one wins.
+                index = match optarg.parse::<usize>() {
+                    Ok(nn) if nn > 0 => Some( nn ),
+                    _ => return Err( CliError::BadArgs( format!( "invalid index for option {} (must be a positive integer): \"{}\"", opt_name, optarg ))),
                 };
-                have_username = true;
-                skip_next_val = true;  // already consumed (as our optarg)
+                pr_trace!("have index from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+            },
+
+            Opt::Sources => {
+                // If specified multiple times, the last one wins.
+                let list = Source::parse_list( &optarg )
+                    .map_err(|msg| CliError::BadArgs( format!( "invalid value for option {}: {}", opt_name, msg )))?;
+                pr_trace!("have credential sources from opt: \"{}\", optarg: \"{}\"", opt_name, optarg);
+                sources = Some( list );
+            },
+
+            Opt::All => {
+                pr_trace!("will print every matching netrc record");
+                all = true;
             },
 
-            "-v" | "--verbose" => {
+            Opt::Verbose => {
                 // Accumulating 'verbose' opt. A single -v opt simply turns
                 // BE_VERBOSE on (enables (additional) info-level messages). Two -v
                 // opts turns on $DEBUGGING, which additionally enables debug-level
@@ -391,7 +1212,7 @@ fn parse_cli_args( args: &Vec<String>) -> Result<CliSuccess, CliError> {
                 // opts to be the first opts on the command line (so they take
                 // effect earlier).
                 if TRACING() {
-                    pr_trace!("tracing already enabled; ignoring extra \"{}\" arg", one_opt);
+                    pr_trace!("tracing already enabled; ignoring extra \"{}\" arg", opt_name);
                 }
                 else {
                     if DEBUGGING() {
@@ -413,244 +1234,1483 @@ fn parse_cli_args( args: &Vec<String>) -> Result<CliSuccess, CliError> {
                     }
                 }
             },
-
-            "--" => {
-                pr_trace!("pseudo opt \"--\" found; will halt command line opts parsing");
-                break;
-            }
-
-            _ => {
-                if one_opt.starts_with("-") {
-                    pr_trace!("Have unrecognized command line option: {}", one_opt);
-                    return Err( CliError::BadArgs( format!( "unrecognized option '{}'; bailing out", one_opt )));
-                }
-
-                pr_trace!("Looking at the first non-option command line argument: \"{}\"{}",
-                          one_opt,
-                          "; will stop parsing the command line opts");
-                index = index - 1;  // We have taken a peek at it, but this one is not yet handled
-                break;
-            },
         }
     }
 
-    pr_trace!("index after: {}", index);
-
-    // There should still be a single command line parameter for us to
-    // consume: the hostname for which we are to search the netrc file.
-
-    if -1 == index {
-        // This should only be possible if the Rust runtime did not put the
-        // program name in args[0], as seeing that would have bumped our index
-        // value up to zero.
-        //
-        pr_debug!("index is -1; no command line opts were provided");
-        index = 0;  // make safe for our usize comparison below
-    }
-
-    // One last cast, to allow us to avoid casting from here on out; we no
-    // longer need the signed (negative) value. This cast is safe b/c index
-    // here is known to be >= 0, and if greater than zero is a value set from
-    // a usize above while parsing the command line opts.
-    //
-    let index: usize = index as usize;
-
-    // Since index holds the value of the last successfully processed
-    // (consumed) command line parameter up until this point, hn_index will
-    // hold the index of "the next" index location, which should be the index
-    // of our hostname parameter (if it was provided by the user).
-    //
-    let hn_index = index + 1;
+    pr_trace!("operands: {:?}", operands);
 
-    if hn_index >= args.len()  // hn_index is out-of-bounds for the provided list of params
+    if [list_mode, show_hostname.is_some(), lint_mode, list_macros_mode, macro_name.is_some(), input_url.is_some(), batch_mode]
+        .iter().filter(|&&on| on).count() > 1
     {
-        return Err( CliError::BadArgs( format!( "required HOSTNAME value not provided; bailing out" )));
+        return Err( CliError::BadArgs( "the --list, --show, --lint, --list-macros, --macro, --url and --batch options are mutually exclusive".to_string() ));
     }
 
-    input_hostname = match args.get( hn_index ) {
-        None => return Err( CliError::BadArgs( format!( "HOSTNAME argument not at index {}", hn_index ))),
-        Some(someval) => someval.clone(),
-    };
-    pr_trace!("provided hostname: \"{}\"", input_hostname);
+    // These modes take no HOSTNAME argument.
+    let no_hostname_mode = list_mode || show_hostname.is_some() || list_macros_mode || macro_name.is_some()
+        || input_url.is_some() || batch_mode;
 
-    // Complain about any remaining command line params
-    //
-    match args.get( hn_index + 1 ) {
-        None => {},  // no extraneous command line parameters provided -- good.
-        Some(ref bogon_hostname) => {
-            return Err( CliError::BadArgs( format!( "Only one HOSTNAME may be provided; have \"{}\", but also got \"{}\"",
-                                                     input_hostname, bogon_hostname )));
+    if no_hostname_mode {
+        if let Some(ref bogon) = operands.first() {
+            return Err( CliError::BadArgs( format!( "no HOSTNAME may be provided with --list, --show, --list-macros, --macro, --url or --batch; got \"{}\"", bogon )));
         }
-    };
-
-    let mut cfg = Config{
-        hostname: input_hostname,  // move ownership
-        username: None,
-    };
-
-    if have_username {
-        cfg.username = Some(input_username);  // move ownership
     }
 
-    Ok(CliSuccess::AdditionalProcessingRequired( cfg ))
-}
-
-fn run_app( args: &Vec<String>) -> Result<(), CliError> {
-
-    pr_trace!("entered: run_app()");
+    if batch_mode && !matches!( output_format, OutputFormat::Text | OutputFormat::Json ) {
+        return Err( CliError::BadArgs( "--batch supports only the text and json output formats".to_string() ));
+    }
 
-    let cfg: Config = match parse_cli_args( &args ) {
+    if all && index.is_some() {
+        return Err( CliError::BadArgs( "the --all and --index options are mutually exclusive".to_string() ));
+    }
 
-        Err(err)  => return Err(err),
+    if all && OutputFormat::Shell == output_format {
+        return Err( CliError::BadArgs( "--all does not support the shell output format".to_string() ));
+    }
 
-        Ok( CliSuccess::ProcessingIsComplete )  => return Ok(()),  // option already handled (e.g., --help)
+    if operands.is_empty() && !no_hostname_mode && !lint_mode {
+        return Err( CliError::BadArgs( "required HOSTNAME value not provided; bailing out".to_string() ));
+    }
 
-        // still have work to do here
-        Ok( CliSuccess::AdditionalProcessingRequired( config ) ) => config,
+    // Only the first operand can name a subcommand. A host that really is
+    // named like one of the subcommands can still be looked up by placing it
+    // after the "--" pseudo opt.
+    let subcmd: Option<&str> = match operands.first() {
+        Some(first) if !saw_end_of_opts && !lint_mode && SUBCOMMANDS.contains( &&first[..] ) => Some( first ),
+        _ => None,
     };
 
-    pr_debug!("Successfully parsed command line options");
-
-    // The hostname for the "wanted" entry from the ~/.netrc file (assuming
-    // such an entry exists).
-    //
-    let wanted_hostname = &cfg.hostname;
-    pr_debug!( "wanted hostname: {}", wanted_hostname );
+    let mode: Mode;
 
-    if let Some(ref vv) = cfg.username {
-        pr_debug!( "wanted username: {}", vv );
+    if list_mode {
+        mode = Mode::List;
     }
-    else {
-        pr_debug!( "wanted username: [none provided (okay)]" );
+    else if batch_mode {
+        mode = Mode::Batch;
     }
+    else if lint_mode {
+        let files: Vec<PathBuf> = operands.iter().map( PathBuf::from ).collect();
+        pr_trace!("files to lint: {:?}", files);
+        mode = Mode::Lint{ files };
+    }
+    else if let Some(hostname) = show_hostname {
+        mode = Mode::Show{ hostname };
+    }
+    else if list_macros_mode {
+        mode = Mode::ListMacros;
+    }
+    else if let Some(name) = macro_name {
+        mode = Mode::Macro{ name };
+    }
+    else if let Some(input_url) = input_url {
+        let url = Url::parse( &input_url )
+            .map_err(|msg| CliError::BadArgs( format!( "unable to parse URL \"{}\": {}", input_url, msg )))?;
+        pr_trace!("parsed URL: {:?}", url);
+        if input_username.is_none() {
+            input_username = url.username.clone();
+        }
+        mode = Mode::UrlLookup{ url };
+    }
+    else if Some("credential") == subcmd {
 
-    pr_debug!("Attempting to locate user's home directory");
-    let home_dir: PathBuf = match home::home_dir() {
-        Some(path) => path,
-        None => return Err( CliError::Msg( format!("was unable to obtain $HOME directory; bailing out") )),
-    };
-    pr_debug!("User's home directory is: {:?}", home_dir );
-
-    {
-        pr_trace!("Translating PathBuf to str (for $HOME value)");
-        let home_dir_str = home_dir.to_str()
-            .ok_or_else(|| CliError::Msg( format!("error translating PathBuf to str; bailing out")))?;
+        // Git credential helper mode: 'credential get|store|erase'.
+        //
+        let op_name = match operands.get( 1 ) {
+            None => return Err( CliError::BadArgs( "required OPERATION value (get, store, or erase) not provided for 'credential'; bailing out".to_string() )),
+            Some(someval) => someval,
+        };
+        let op = CredentialOp::from_name( op_name )
+            .ok_or_else(|| CliError::BadArgs( format!( "unrecognized 'credential' OPERATION: \"{}\"", op_name )))?;
+        pr_trace!("git credential helper operation: {:?}", op);
+
+        if let Some(ref bogon) = operands.get( 2 ) {
+            return Err( CliError::BadArgs( format!( "unexpected argument after 'credential {}': \"{}\"", op_name, bogon )));
+        }
 
-        if 0 == home_dir_str.len() {
-            return Err( CliError::Msg( format!("HOME directory name is the empty string; bailing out")));
+        mode = Mode::Credential( op );
+    }
+    else if Some("askpass") == subcmd {
+        let prompt = match operands.get( 1 ) {
+            None => return Err( CliError::BadArgs( "required PROMPT value not provided for 'askpass'; bailing out".to_string() )),
+            Some(someval) => someval.clone(),
+        };
+        if let Some(ref bogon) = operands.get( 2 ) {
+            return Err( CliError::BadArgs( format!( "unexpected argument after 'askpass' PROMPT: \"{}\"", bogon )));
         }
+        mode = Mode::Askpass{ prompt };
     }
+    else if subcmd.is_some() {
 
-    pr_trace!("Constructing path to ~/.netrc file in user's $HOME");
+        // 'set', 'remove' or 'rename-host'
+        //
+        let (edit_mode, edit_username) = parse_edit_args( &operands )?;
+        if let Some(username) = edit_username {
+            if input_username.is_some() {
+                return Err( CliError::BadArgs( "At most one -u (--user=USER) opt may be provided".to_string() ));
+            }
+            input_username = Some( username );
+        }
+        mode = edit_mode;
+    }
+    else {
+        let input_hostname = operands.remove( 0 );
+        pr_trace!("provided hostname: \"{}\"", input_hostname);
 
-    // FIXME: Make this more generic -- use libcurl as a model (support '_netrc', too, etc.).
-    //
-    let netrc_fpath: PathBuf = home_dir.join(".netrc");
+        // Complain about any remaining command line params
+        //
+        if let Some(ref bogon_hostname) = operands.first() {
+            return Err( CliError::BadArgs( format!( "Only one HOSTNAME may be provided; have \"{}\", but also got \"{}\"",
+                                                     input_hostname, bogon_hostname )));
+        }
 
-    pr_trace!("Constructed path to ~/.netrc file in user's $HOME is: {:?}", &netrc_fpath );
+        mode = Mode::Lookup{ hostname: input_hostname };  // move ownership
+    }
 
-    pr_debug!("Ensuring that user's ~/.netrc file exists");
-    if !netrc_fpath.exists() {
-        return Err( CliError::Msg( format!( "file does not exist: {:?}; bailing out", &netrc_fpath )));
+    if all && !matches!( mode, Mode::Lookup{..} | Mode::UrlLookup{..} ) {
+        return Err( CliError::BadArgs( "--all may be used only to look up a HOSTNAME or a --url".to_string() ));
     }
 
-    pr_debug!("Ensuring that user's ~/.netrc file is a regular file");
-    if !netrc_fpath.is_file() {
-        return Err( CliError::Msg( format!( "{:?} exists, but is not a file; bailing out", &netrc_fpath)));
+    if sources.is_some() && !matches!( mode, Mode::Lookup{..} | Mode::UrlLookup{..} ) {
+        return Err( CliError::BadArgs( "--sources may be used only to look up a HOSTNAME or a --url".to_string() ));
     }
 
-    let netrc_file: File = match File::open( &netrc_fpath ) {
-        Ok(opened_file) => opened_file,
-        Err(err) => {
-            return Err( CliError::IoErrorW( format!( "Was unable to open the user's netrc file: {:?}",
-                                                      &netrc_fpath),
-                                            err ));
+    if emit.is_some() {
+        if !matches!( mode, Mode::Lookup{..} | Mode::UrlLookup{..} ) || all {
+            return Err( CliError::BadArgs( "--emit may be used only to look up the record for a HOSTNAME or a --url".to_string() ));
         }
-    };
+        if output_format_given || !fields.is_empty() {
+            return Err( CliError::BadArgs( "--emit may not be combined with --output-format or --field".to_string() ));
+        }
+        if !show_password {
+            return Err( CliError::BadArgs( "refusing to emit the password without --show-password".to_string() ));
+        }
+    }
 
-    let netrc_br = BufReader::new( netrc_file );
+    if all && sources.is_some() {
+        return Err( CliError::BadArgs( "the --all and --sources options are mutually exclusive".to_string() ));
+    }
 
-    pr_debug!("Parsing user's netrc file");
-    let netrc_obj = match Netrc::parse( netrc_br ) {
-        Ok(parsed_obj) => parsed_obj,
-        Err(err) => {
-            return Err( CliError::NetrcError( format!( "Was unable to parse the user's netrc file: {:?}",
-                                                        &netrc_fpath),
-                                              err ));
-        }
-    };
-    pr_debug!("Successfully parsed user's netrc file");
+    if fields.is_empty() {
+        fields.push( Field::Login );
+    }
+    fields.sort();
+    fields.dedup();
 
-// DEBUG go
-    // pr_trace!( "netrc_obj.hosts: {:?}", netrc_obj.hosts );
-// DEBUG end
+    if fields.contains( &Field::Password ) && !show_password {
+        return Err( CliError::BadArgs( "refusing to print the password field without --show-password".to_string() ));
+    }
 
-    for (ref one_hostname, ref one_machine) in netrc_obj.hosts {
+    let cfg = Config{
+        mode,
+        username: input_username,
+        account,
+        index,
+        all,
+        sources,
+        use_default,
+        helper_default,
+        fields,
+        show_password,
+        allow_password_cmd,
+        output_format,
+        emit,
+        netrc_files,
+        decrypt_with,
+        strict_perms,
+    };
 
-        // CAREFUL: Do no just print the one_machine (netrc::Machine) struct
-        //          because that would show the password field.
-        pr_trace!( "one netrc record" );
-        pr_trace!( "  one host.name: {}",   one_hostname );
-        pr_trace!( "  one host.mach: {:?}", fmt_netrc_machine( &one_machine ) );  // sanitize display
+    Ok(CliSuccess::AdditionalProcessingRequired( Box::new( cfg )))
+}
 
-        pr_debug!( "Checking if record hostname (\"{}\") matches wanted hostname (\"{}\")",
-                    one_hostname, wanted_hostname);
+// The options of the 'set', 'remove' and 'rename-host' subcommands.
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditOpt {
+    Login,
+    PasswordStdin,
+    User,
+}
 
-        // if "api.github.com" == one_hostname {
-        if !(wanted_hostname == &one_hostname[..]) {
-            pr_debug!( "netrc hostname does not match: {}", one_hostname );
-            continue;
-        }
+const SET_OPTIONS: &[OptSpec<EditOpt>] = &[
+    OptSpec::new( EditOpt::Login,         None, Some("login"),          HasArg::Required ),
+    OptSpec::new( EditOpt::PasswordStdin, None, Some("password-stdin"), HasArg::No ),
+];
 
-        pr_debug!( "netrc hostname matches: {}", one_hostname );
+const REMOVE_OPTIONS: &[OptSpec<EditOpt>] = &[
+    OptSpec::new( EditOpt::User, Some('u'), Some("user"), HasArg::Required ),
+];
 
-        if let Some(_) = cfg.username {}
-        else {
-            // No username value was provided to constrain the match, so we've
-            // found our match.
-            println!("{}", &one_machine.login[..] );
-            return Ok(());
-        }
+// Parses the arguments of the 'set', 'remove' and 'rename-host' subcommands,
+// starting with the name of the subcommand itself. As for the global options,
+// the options of these subcommands may follow their HOST argument(s).
+//
+// Returns the mode, along with the USER of any '--user' option (which has the
+// same meaning as the global one).
+//
+fn parse_edit_args( sub_args: &[String] ) -> Result<(Mode, Option<String>), CliError> {
 
-        if let Some(ref wanted_username) = cfg.username {
+    let subcmd = &sub_args[0][..];
 
-            // A username value was provided, so we need to match that, as well
+    let options: &[OptSpec<EditOpt>] = match subcmd {
+        "set"    => SET_OPTIONS,
+        "remove" => REMOVE_OPTIONS,
+        _        => &[],
+    };
 
-            pr_debug!( "Checking if record username (\"{}\") matches wanted username (\"{}\")",
-                       &one_machine.login[..], wanted_username);
+    let mut positionals: Vec<String> = Vec::new();
+    let mut login: Option<String> = None;
+    let mut username: Option<String> = None;
+    let mut password_stdin = false;
 
-            if wanted_username == &(one_machine.login) {
-                pr_debug!( "netrc username matches: {}", &(one_machine.login) );
-                println!("{}", &one_machine.login[..] );
-                return Ok(());
-            }
+    for item in getopt::Parser::new( &sub_args[1..], options ) {
 
-            pr_debug!( "netrc username does not match: {}", &(one_machine.login) );
-            continue;
+        match item.map_err(|err| CliError::BadArgs( format!( "'{}': {}", subcmd, err )))? {
+            Item::Operand(operand) => positionals.push( operand ),
+            Item::EndOfOpts => (),
+            Item::Opt{ id: EditOpt::Login, value, .. } => login = value,
+            Item::Opt{ id: EditOpt::PasswordStdin, .. } => password_stdin = true,
+            Item::Opt{ id: EditOpt::User, value, .. } => username = value,
         }
     }
 
-    // If we are falling through here, then none of the netrc records matched
-    // the specified hostname (or hostname, username pair).
-
-    if let Some(ref wanted_username) = &(cfg.username) {
-        return Err( CliError::NoMatchingNetrcRecord2{ hostname: wanted_hostname.clone(),
-                                                      username: wanted_username.clone() } );
+    let want_positionals = if "rename-host" == subcmd { 2 } else { 1 };
+    if positionals.len() != want_positionals {
+        return Err( CliError::BadArgs( format!( "'{}' requires {} argument(s), but got {}: {:?}",
+                                                 subcmd, want_positionals, positionals.len(), positionals )));
     }
 
-    return Err( CliError::NoMatchingNetrcRecord1{ hostname: wanted_hostname.clone() } );
-}
-
+    let mode = match subcmd {
+        "set" => {
+            let login = login
+                .ok_or_else(|| CliError::BadArgs( "the --login=LOGIN option is required for 'set'".to_string() ))?;
+            if !password_stdin {
+                // The password is never accepted on the command line, where
+                // it would be visible to other users (e.g., via ps(1)).
+                return Err( CliError::BadArgs( "the --password-stdin option is required for 'set'".to_string() ));
+            }
+            Mode::Set{ hostname: positionals.remove(0), login }
+        },
+        "remove" => Mode::Remove{ hostname: positionals.remove(0) },
+        _ => Mode::RenameHost{ old_hostname: positionals.remove(0), new_hostname: positionals.remove(0) },
+    };
+
+    Ok( (mode, username) )
+}
+
+// Returns the value of the given field of a netrc record as a string. The
+// 'hostname' is the name from the record's 'machine' entry, or None for the
+// 'default' record. Absent fields yield the empty string.
+//
+// The value is wrapped in a Secret whatever the field, so that the password
+// is never held in a plain String on its way to the output.
+//
+fn field_value( field: Field, hostname: Option<&str>, machine: &netrc::Machine ) -> Secret {
+    match field {
+        Field::Machine  => Secret::from( hostname.unwrap_or("") ),
+        Field::Login    => Secret::from( machine.login.as_str() ),
+        Field::Password => machine.password.clone().unwrap_or_default(),
+        Field::Account  => Secret::from( machine.account.as_deref().unwrap_or("") ),
+        Field::Port     => Secret::new( machine.port.map(|port| port.to_string()).unwrap_or_default() ),
+    }
+}
+
+// Returns 'machine' as it is to be printed in the output selected in 'cfg':
+// with its password filled in (see with_password()) only if that output
+// includes the password.
+//
+fn machine_for_output<'a>( cfg: &Config, machine: &'a netrc::Machine ) -> Result<Cow<'a, netrc::Machine>, CliError> {
+
+    let wants_password = cfg.emit.is_some()
+        || cfg.fields.contains( &Field::Password )
+        || (OutputFormat::Json == cfg.output_format && cfg.show_password);
+
+    match wants_password {
+        true  => with_password( cfg, machine ),
+        false => Ok( Cow::Borrowed( machine )),
+    }
+}
+
+// Returns the program of the command 'cmd' (a 'password_cmd' or a decryption
+// command), for logging; "?" if the command cannot be split into words. Only
+// the program is logged, as the arguments may include a secret (e.g., a vault
+// token).
+//
+fn cmd_program( cmd: &str ) -> String {
+    password_cmd::split_words( cmd ).ok()
+        .and_then(|words| words.into_iter().next())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+// Returns 'machine' with its password filled in by running its 'password_cmd'
+// (see the 'password_cmd' module), if it has one and no 'password'. Running
+// the command requires '--allow-password-cmd'; without it, such a record is
+// an error. So that a login-only lookup never runs the command, this is
+// called only when the password is actually needed.
+//
+fn with_password<'a>( cfg: &Config, machine: &'a netrc::Machine ) -> Result<Cow<'a, netrc::Machine>, CliError> {
+
+    let cmd = match (&machine.password, &machine.password_cmd) {
+        (None, Some(cmd)) => cmd,
+        _ => return Ok( Cow::Borrowed( machine )),
+    };
+    if !cfg.allow_password_cmd {
+        return Err( CliError::Msg( format!( "the matched netrc record (line {}) has a 'password_cmd', which is run only with --allow-password-cmd",
+                                            machine.lnum )));
+    }
+
+    pr_debug!( "running the 'password_cmd' of the matched netrc record (line {}): program \"{}\"",
+               machine.lnum, cmd_program( cmd ));
+    let password = password_cmd::run( cmd, password_cmd::DEFAULT_TIMEOUT )
+        .map_err(|err| CliError::Msg( format!( "unable to get the password of the matched netrc record (line {}): {}",
+                                               machine.lnum, err )))?;
+
+    let mut machine = machine.clone();
+    machine.password = Some( password );
+    Ok( Cow::Owned( machine ))
+}
+
+// Prints the matched netrc record on stdout in the output format selected in
+// 'cfg'. The 'hostname' is the name from the record's 'machine' entry, or
+// None for the 'default' record.
+//
+// For all but the JSON output format, only the fields selected in 'cfg' are
+// printed. The JSON object always contains the full record, but the password
+// is redacted unless '--show-password' was specified.
+//
+// With '--emit', the login and password are printed as configuration for
+// curl or wget, instead (see the 'emit' module).
+//
+fn print_record( cfg: &Config, hostname: Option<&str>, machine: &netrc::Machine ) -> Result<(), CliError> {
+
+    let machine = machine_for_output( cfg, machine )?;
+    let machine = &*machine;
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if let Some(emit) = cfg.emit {
+        pr_trace!("emitting record as: {:?}", emit);
+        let password = match machine.password {
+            Some(ref password) => password.expose_secret(),
+            None => return Err( CliError::Msg( "the matched record has no password to emit".to_string() )),
+        };
+        let rendered = emit.render( &machine.login, password )
+            .map_err(|msg| CliError::Msg( format!( "unable to emit the matched record: {}", msg )))?;
+        out.write_all( rendered.expose_secret().as_bytes() )?;
+        return Ok(());
+    }
+
+    pr_trace!("printing record in output format: {:?}", cfg.output_format);
+
+    match cfg.output_format {
+
+        OutputFormat::Text => {
+            for field in &cfg.fields {
+                writeln!( out, "{}", field_value( *field, hostname, machine ).expose_secret() )?;
+            }
+        },
+
+        OutputFormat::Nul => {
+            for field in &cfg.fields {
+                write!( out, "{}\0", field_value( *field, hostname, machine ).expose_secret() )?;
+            }
+        },
+
+        OutputFormat::Shell => {
+            for field in &cfg.fields {
+                let var_name = match field {
+                    Field::Machine  => "NETRC_MACHINE",
+                    Field::Login    => "NETRC_LOGIN",
+                    Field::Password => "NETRC_PASSWORD",
+                    Field::Account  => "NETRC_ACCOUNT",
+                    Field::Port     => "NETRC_PORT",
+                };
+                let quoted = Secret::new( shell_quote( field_value( *field, hostname, machine ).expose_secret() ));
+                writeln!( out, "{}={}", var_name, quoted.expose_secret() )?;
+            }
+        },
+
+        OutputFormat::Json => {
+            writeln!( out, "{}", record_json( cfg, hostname, machine ).expose_secret() )?;
+        },
+    }
+
+    Ok(())
+}
+
+// Implements the '--all' option: prints every 'machine' record that matches
+// 'query', in file order, one per line (for the nul output format, each field
+// is terminated by a NUL byte). With '-O json', the records are printed as a
+// single JSON array. Only when there is no such record is the 'default' record
+// (unless --no-default) printed, as it is the one a lookup would select.
+//
+fn print_all_records( cfg: &Config, query: &Query, loaded: &Loaded ) -> Result<(), CliError> {
+
+    let mut found = query.find_all( loaded );
+    if found.is_empty() {
+        found.push( query.find( loaded )? );
+    }
+    pr_debug!( "found {} matching record(s)", found.len() );
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if OutputFormat::Json == cfg.output_format {
+        write!( out, "[" )?;
+        for (idx, mm) in found.iter().enumerate() {
+            let machine = machine_for_output( cfg, mm.machine )?;
+            let sep = if 0 == idx { "" } else { "," };
+            write!( out, "{}{}", sep, record_json( cfg, mm.hostname, &machine ).expose_secret() )?;
+        }
+        writeln!( out, "]" )?;
+        return Ok(());
+    }
+
+    for mm in &found {
+        note_match( mm );
+        let machine = machine_for_output( cfg, mm.machine )?;
+        let values: Vec<Secret> = cfg.fields.iter()
+            .map(|field| field_value( *field, mm.hostname, &machine ))
+            .collect();
+        match cfg.output_format {
+            OutputFormat::Nul => {
+                for value in &values {
+                    write!( out, "{}\0", value.expose_secret() )?;
+                }
+            },
+            _ => write_tab_separated( &mut out, &values )?,
+        }
+    }
+
+    Ok(())
+}
+
+// Writes 'values' on a line of their own, separated by tabs.
+//
+fn write_tab_separated( out: &mut dyn Write, values: &[Secret] ) -> io::Result<()> {
+    for (idx, value) in values.iter().enumerate() {
+        let sep = if 0 == idx { "" } else { "\t" };
+        write!( out, "{}{}", sep, value.expose_secret() )?;
+    }
+    writeln!( out )
+}
+
+// Returns the full netrc record as a JSON object, with the password redacted
+// unless '--show-password' was specified. As it may contain the password, the
+// object is wrapped in a Secret.
+//
+fn record_json( cfg: &Config, hostname: Option<&str>, machine: &netrc::Machine ) -> Secret {
+
+    let password = match (&machine.password, &machine.password_cmd) {
+        (None, None)                         => Secret::from( "null" ),
+        (Some(ref vv), _) if cfg.show_password => Secret::new( json_quote( vv.expose_secret() )),
+        _                                    => Secret::new( json_quote( secret::REDACTED )),
+    };
+    Secret::new( format!( "{{\"machine\":{},\"default\":{},\"login\":{},\"password\":{},\"account\":{},\"port\":{}}}",
+                          hostname.map_or( "null".to_string(), json_quote ),
+                          hostname.is_none(),
+                          json_quote( &machine.login ),
+                          password.expose_secret(),
+                          machine.account.as_deref().map_or( "null".to_string(), json_quote ),
+                          machine.port.map_or( "null".to_string(), |port| port.to_string() )))
+}
+
+// Checks the permissions and ownership of the netrc file (see
+// lookup::file_perms_problem()).
+//
+fn netrc_file_perms_problem( netrc_fpath: &Path ) -> Result<Option<String>, CliError> {
+    lookup::file_perms_problem( netrc_fpath )
+        .map_err(|err| CliError::IoErrorW( format!( "Was unable to stat the netrc file: {:?}", netrc_fpath ), err ))
+}
+
+// Determines the netrc file(s) to be searched. The first of these that is set
+// wins:
+//
+//     1. the '--netrc-file=FILE' command line option
+//     2. the NETRC environment variable (as honored by recent versions of curl)
+//     3. the '.netrc' file in the user's $HOME directory
+//
+// Either of the first two may name a colon-separated list of files.
+//
+fn netrc_source( cfg: &Config ) -> Result<NetrcSource, CliError> {
+
+    let source = match cfg.netrc_files {
+        Some(ref from_opt) => {
+            pr_debug!("using netrc file(s) from --netrc-file option: {}", from_opt);
+            NetrcSource::path_list( from_opt )
+        },
+        None => NetrcSource::from_env()?,
+    };
+
+    if source.file_paths().is_empty() {
+        return Err( CliError::Msg( "the list of netrc files to search is empty; bailing out".to_string() ));
+    }
+    pr_trace!("netrc file(s) to search, in order: {:?}", source.file_paths());
+
+    let decrypt = decrypt_setting( cfg );
+    for netrc_fpath in source.file_paths() {
+        if let Some(cmd) = decrypt.command_for( netrc_fpath ) {
+            pr_debug!("netrc file {:?} will be decrypted with program: \"{}\"", netrc_fpath, cmd_program( cmd ));
+        }
+    }
+
+    let perms_check = if cfg.strict_perms { PermsCheck::Strict } else { PermsCheck::Warn };
+    Ok( source.perms_check( perms_check ).decrypt( decrypt ))
+}
+
+// Which netrc files are decrypted as they are read, and how: with the
+// '--decrypt-with=CMD' option, every file, with CMD; otherwise, the .gpg and
+// .asc files, with gpg (see the 'decrypt' module).
+//
+fn decrypt_setting( cfg: &Config ) -> Decrypt {
+    match cfg.decrypt_with {
+        Some(ref cmd) => Decrypt::With( cmd.clone() ),
+        None          => Decrypt::Auto,
+    }
+}
+
+fn netrc_file_paths( cfg: &Config ) -> Result<Vec<PathBuf>, CliError> {
+    Ok( netrc_source( cfg )?.file_paths().to_vec() )
+}
+
+// Reads and parses each of the netrc files to be searched (see
+// netrc_source()), in order, warning about any that have unsafe permissions
+// (with '--no-strict-perms').
+//
+fn load_netrc_files( cfg: &Config ) -> Result<Loaded, CliError> {
+
+    let loaded = netrc_source( cfg )?.load()?;
+
+    for warning in &loaded.warnings {
+        pr_warning!( "{}", warning );
+    }
+    for (ref netrc_fpath, _) in &loaded.files {
+        pr_debug!("Successfully parsed netrc file: {:?}", netrc_fpath);
+    }
+
+    Ok( loaded )
+}
+
+// Builds the query for the record for 'wanted_hostname', honoring the '-u',
+// '--account', '--index' and '--no-default' options.
+//
+fn host_query( cfg: &Config, wanted_hostname: &str, wanted_username: Option<&str> ) -> Query {
+
+    let mut query = Query::host( wanted_hostname ).default_fallback( cfg.use_default );
+    if let Some(wanted_username) = wanted_username {
+        query = query.user( wanted_username );
+    }
+    select_account( cfg, query )
+}
+
+// Narrows 'query' by the '--account' and '--index' options, if given.
+//
+fn select_account( cfg: &Config, mut query: Query ) -> Query {
+
+    if let Some(ref wanted_account) = cfg.account {
+        query = query.account( wanted_account.clone() );
+    }
+    if let Some(wanted_index) = cfg.index {
+        query = query.index( wanted_index );
+    }
+    query
+}
+
+// Notes (with -v) where the record found by a lookup came from.
+//
+fn note_match( found: &Match ) {
+    match found.hostname {
+        Some(_) => pr_info!( "matching 'machine' record (line {}) found in netrc file: {:?}", found.line(), found.file ),
+        None    => pr_info!( "matching 'default' record (line {}) found in netrc file: {:?}", found.line(), found.file ),
+    }
+    // CAREFUL: Do no just print the netrc::Machine struct because that would
+    //          show the password field.
+    pr_trace!( "matched record: {:?}", fmt_netrc_machine( found.machine ) );  // sanitize display
+}
+
+
+// Implements the '--list' and '--show=HOST' options: prints every netrc
+// record (or, for '--show', just the records that could apply to HOST) in
+// file order, noting the file and line number of each. Passwords are always
+// redacted.
+//
+// Each record is annotated with whether a lookup of its hostname (for
+// '--show', of HOST) would select it, taking into account the '-u' and
+// '--no-default' options. A 'machine' record that would not be selected is
+// shadowed by an earlier record for the same host; a 'default' record is
+// selected only for hosts that have no matching 'machine' record.
+//
+// Returns NoMatchingNetrcRecord* errors when '--show' finds no applicable
+// record.
+//
+fn list_records( cfg: &Config, show_hostname: Option<&str> ) -> Result<(), CliError> {
+
+    let loaded = load_netrc_files( cfg )?;
+
+    let wanted_username = cfg.username.as_deref();
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    let json = OutputFormat::Json == cfg.output_format;
+    if json {
+        write!( out, "[" )?;
+    }
+
+    let selected_default = match cfg.use_default {
+        true  => host_query( cfg, "", wanted_username ).find_default( &loaded ),
+        false => None,
+    };
+
+    // A 'default' record is only selected for a '--show' HOST that has no
+    // matching 'machine' record.
+    let default_applies = match show_hostname {
+        Some(wanted_hostname) => host_query( cfg, wanted_hostname, wanted_username ).find_machine( &loaded ).is_none(),
+        None => true,
+    };
+
+    // For each listed record: (record number, file, hostname, machine, selected)
+    let mut listed: Vec<(usize, &Path, Option<&str>, &netrc::Machine, bool)> = Vec::new();
+
+    let mut record_number: usize = 0;
+
+    for (ref netrc_fpath, ref netrc_obj) in &loaded.files {
+
+        // The records of each file, in file order. (The 'default' record
+        // should be last, but is not required to be.)
+        let mut records: Vec<(Option<&str>, &netrc::Machine)> = netrc_obj.hosts.iter()
+            .map(|(hostname, machine)| (Some(&hostname[..]), machine))
+            .chain( netrc_obj.default.iter().map(|machine| (None, machine)) )
+            .collect();
+        records.sort_by_key(|(_, machine)| machine.lnum);
+
+        for (hostname, machine) in records {
+            record_number += 1;
+
+            let selected = match hostname {
+                Some(one_hostname) => {
+                    if let Some(wanted_hostname) = show_hostname {
+                        if !wanted_hostname.eq_ignore_ascii_case( one_hostname ) {
+                            continue;
+                        }
+                    }
+                    match host_query( cfg, one_hostname, wanted_username ).find( &loaded ) {
+                        Ok(found) => std::ptr::eq( found.machine, machine ),
+                        Err(_) => false,
+                    }
+                },
+                None => default_applies && match selected_default {
+                    Some(ref found) => std::ptr::eq( found.machine, machine ),
+                    None => false,
+                },
+            };
+
+            listed.push( (record_number, netrc_fpath, hostname, machine, selected) );
+        }
+    }
+
+    for (nth, (number, netrc_fpath, hostname, machine, selected)) in listed.iter().enumerate() {
+
+        if json {
+            if nth > 0 {
+                write!( out, "," )?;
+            }
+            write!( out, "{{\"record\":{},\"file\":{},\"line\":{},\"machine\":{},\"default\":{},\"login\":{},\"password\":{},\"account\":{},\"port\":{},\"selected\":{}}}",
+                    number,
+                    json_quote( &netrc_fpath.to_string_lossy() ),
+                    machine.lnum,
+                    hostname.map_or( "null".to_string(), json_quote ),
+                    hostname.is_none(),
+                    json_quote( &machine.login ),
+                    match machine.password.is_some() || machine.password_cmd.is_some() {
+                        true  => json_quote( secret::REDACTED ),
+                        false => "null".to_string(),
+                    },
+                    machine.account.as_deref().map_or( "null".to_string(), json_quote ),
+                    machine.port.map_or( "null".to_string(), |port| port.to_string() ),
+                    selected )?;
+            continue;
+        }
+
+        let login_mismatch = wanted_username.map_or(false, |wanted| wanted != machine.login);
+
+        let note = match (hostname, selected) {
+            (Some(_), true)  => "selected",
+            (Some(_), false) if login_mismatch => "login does not match --user",
+            (Some(_), false) => "shadowed by an earlier record",
+            (None, true)     => "selected for hosts with no matching 'machine' record",
+            (None, false) if !cfg.use_default => "ignored (--no-default)",
+            (None, false) if login_mismatch => "login does not match --user",
+            (None, false)    => "not selected",
+        };
+
+        writeln!( out, "#{} {}:{}: {}: {}  [{}]",
+                  number,
+                  netrc_fpath.display(),
+                  machine.lnum,
+                  hostname.map_or( "default".to_string(), |hn| format!( "machine {}", hn )),
+                  fmt_netrc_machine( machine ),  // sanitize display
+                  note )?;
+    }
+
+    if json {
+        writeln!( out, "]" )?;
+    }
+
+    if let Some(wanted_hostname) = show_hostname {
+        if !listed.iter().any(|(_, _, _, _, selected)| *selected) {
+            if let Some(ref wanted_username) = cfg.username {
+                return Err( CliError::NoMatchingNetrcRecord2{ hostname: wanted_hostname.to_string(),
+                                                              username: wanted_username.clone() } );
+            }
+            return Err( CliError::NoMatchingNetrcRecord1{ hostname: wanted_hostname.to_string() } );
+        }
+    }
+
+    Ok(())
+}
+
+
+// Prints the names of all of the netrc 'macdef' macros, one per line, or
+// (given 'wanted_name') the body of the first macro with that name, exactly as
+// written in the netrc file.
+//
+// Returns NoMatchingMacro when no macro is named 'wanted_name'.
+//
+fn print_macros( cfg: &Config, wanted_name: Option<&str> ) -> Result<(), CliError> {
+
+    let loaded = load_netrc_files( cfg )?;
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    for (ref netrc_fpath, ref netrc_obj) in &loaded.files {
+        for (ref name, ref body) in &netrc_obj.macros {
+            match wanted_name {
+                None => writeln!( out, "{}", name )?,
+                Some(wanted_name) if wanted_name == name => {
+                    pr_debug!( "found macro \"{}\" in netrc file: {:?}", name, netrc_fpath );
+                    write!( out, "{}", body )?;
+                    return Ok(());
+                },
+                Some(_) => (),
+            }
+        }
+    }
+
+    if let Some(wanted_name) = wanted_name {
+        return Err( CliError::NoMatchingMacro{ name: wanted_name.to_string() } );
+    }
+
+    Ok(())
+}
+
+
+// One '--lint' diagnostic: (file, (line, column), severity, message). A problem
+// with the file as a whole (e.g., its permissions) has no line or column.
+//
+type FileDiagnostic<'a> = (&'a Path, Option<(usize, usize)>, Severity, String);
+
+// Checks netrc files for problems ('--lint'), printing each diagnostic on
+// stdout. When 'files' is empty, the netrc files that a lookup would read are
+// checked, including their permissions and ownership; files named on the
+// command line (likely copies kept in a dotfiles repository) are checked for
+// content only.
+//
+// Returns LintProblemsFound if anything was reported, and NetrcFileNotFound
+// if a file named on the command line (or every file of a list of them) does
+// not exist, as a lookup would.
+//
+fn lint_files( cfg: &Config, files: &[PathBuf] ) -> Result<(), CliError> {
+
+    let check_perms = files.is_empty();
+    let netrc_fpaths: Vec<PathBuf> = if check_perms { netrc_file_paths( cfg )? } else { files.to_vec() };
+
+    // As for lookups, the netrc files that do not exist are skipped when
+    // searching a list of them, but at least one of them must exist. Files
+    // named on the command line must exist.
+    let skip_missing = check_perms && netrc_fpaths.len() > 1;
+
+    let decrypt = decrypt_setting( cfg );
+
+    let mut diags: Vec<FileDiagnostic> = Vec::new();
+    let mut linted = 0;
+
+    for netrc_fpath in &netrc_fpaths {
+
+        if !netrc_fpath.exists() {
+            if skip_missing {
+                pr_debug!("netrc file does not exist: {:?}; skipping it", netrc_fpath);
+                continue;
+            }
+            return Err( CliError::NetrcFileNotFound{ paths: vec![ netrc_fpath.clone() ] } );
+        }
+        if !netrc_fpath.is_file() {
+            return Err( CliError::NetrcNotAFile{ path: netrc_fpath.clone() } );
+        }
+        linted += 1;
+
+        let netrc_file: File = File::open( netrc_fpath )
+            .map_err(|err| CliError::IoErrorW( format!( "Was unable to open the netrc file: {:?}", netrc_fpath ), err ))?;
+
+        if check_perms {
+            if let Some(reason) = netrc_file_perms_problem( netrc_fpath )? {
+                diags.push( (netrc_fpath, None, Severity::Warning, reason) );
+            }
+        }
+
+        pr_debug!("Linting netrc file: {:?}", netrc_fpath);
+        let found = match decrypt.command_for( netrc_fpath ) {
+            Some(cmd) => {
+                pr_debug!("decrypting netrc file {:?} with program: \"{}\"", netrc_fpath, cmd_program( cmd ));
+                let plaintext = decrypt::decrypt( cmd, netrc_file, decrypt::DEFAULT_TIMEOUT )
+                    .map_err(|err| CliError::NetrcDecryptError{ path: netrc_fpath.clone(), msg: format!( "{}", err ) })?;
+                lint::lint( plaintext.expose_secret() )
+            },
+            None => lint::lint( BufReader::new( netrc_file )),
+        };
+        let found = found.map_err(|err| netrc_error( netrc_fpath, err ))?;
+
+        for diag in found {
+            diags.push( (netrc_fpath, Some( (diag.lnum, diag.col) ), diag.severity, diag.message) );
+        }
+    }
+
+    if 0 == linted {
+        return Err( CliError::NetrcFileNotFound{ paths: netrc_fpaths } );
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if OutputFormat::Json == cfg.output_format {
+        write!( out, "[" )?;
+        for (nth, (netrc_fpath, pos, severity, message)) in diags.iter().enumerate() {
+            if nth > 0 {
+                write!( out, "," )?;
+            }
+            write!( out, "{{\"file\":{},\"line\":{},\"column\":{},\"severity\":{},\"message\":{}}}",
+                    json_quote( &netrc_fpath.to_string_lossy() ),
+                    pos.map_or( "null".to_string(), |(lnum, _)| lnum.to_string() ),
+                    pos.map_or( "null".to_string(), |(_, col)| col.to_string() ),
+                    json_quote( &severity.to_string() ),
+                    json_quote( message ))?;
+        }
+        writeln!( out, "]" )?;
+    }
+    else {
+        for (netrc_fpath, pos, severity, message) in &diags {
+            match pos {
+                Some((lnum, col)) => writeln!( out, "{}:{}:{}: {}: {}", netrc_fpath.display(), lnum, col, severity, message )?,
+                None              => writeln!( out, "{}: {}: {}", netrc_fpath.display(), severity, message )?,
+            }
+        }
+    }
+
+    if !diags.is_empty() {
+        return Err( CliError::LintProblemsFound{ count: diags.len() } );
+    }
+
+    Ok(())
+}
+
+
+// Creates (or truncates) the file at 'fpath' for writing, with mode 0600.
+//
+fn create_private_file( fpath: &Path, create_new: bool ) -> io::Result<File> {
+
+    let mut opts = fs::OpenOptions::new();
+    opts.write( true );
+    if create_new {
+        opts.create_new( true );
+    }
+    else {
+        opts.create( true ).truncate( true );
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode( 0o600 );
+    }
+
+    let file = opts.open( fpath )?;
+
+    // The mode given above applies only if the file is newly created.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions( fs::Permissions::from_mode( 0o600 ))?;
+    }
+
+    Ok( file )
+}
+
+// Replaces the netrc file at 'netrc_fpath' with 'content' atomically: the new
+// content is written to a temporary file in the same directory, which is then
+// renamed over the original. If 'backup' is true, the original is first copied
+// to FILE.bak. All of the files written have mode 0600.
+//
+fn write_netrc_file( netrc_fpath: &Path, content: &str, backup: bool ) -> Result<(), CliError> {
+
+    let dir: &Path = match netrc_fpath.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_name = netrc_fpath.file_name()
+        .ok_or_else(|| CliError::Msg( format!( "not a file path: {:?}", netrc_fpath )))?;
+
+    if backup {
+        let mut bak_fpath = netrc_fpath.as_os_str().to_os_string();
+        bak_fpath.push( ".bak" );
+        let bak_fpath = PathBuf::from( bak_fpath );
+
+        pr_debug!("Backing up netrc file {:?} to: {:?}", netrc_fpath, bak_fpath);
+        let mut orig = File::open( netrc_fpath )
+            .map_err(|err| CliError::IoErrorW( format!( "Was unable to open the netrc file: {:?}", netrc_fpath ), err ))?;
+        let mut bak = create_private_file( &bak_fpath, false )
+            .map_err(|err| CliError::IoErrorW( format!( "Was unable to create the backup file: {:?}", bak_fpath ), err ))?;
+        io::copy( &mut orig, &mut bak )
+            .and_then(|_| bak.sync_all())
+            .map_err(|err| CliError::IoErrorW( format!( "Was unable to write the backup file: {:?}", bak_fpath ), err ))?;
+    }
+
+    let mut tmp_name = OsString::from( "." );
+    tmp_name.push( file_name );
+    tmp_name.push( format!( ".tmp.{}", process::id() ));
+    let tmp_fpath = dir.join( tmp_name );
+
+    pr_debug!("Writing new netrc content to temporary file: {:?}", tmp_fpath);
+    let written = create_private_file( &tmp_fpath, true )
+        .and_then(|mut tmp| {
+            tmp.write_all( content.as_bytes() )?;
+            tmp.sync_all()
+        })
+        .and_then(|_| fs::rename( &tmp_fpath, netrc_fpath ));
+
+    if let Err(err) = written {
+        let _ = fs::remove_file( &tmp_fpath );  // best effort
+        return Err( CliError::IoErrorW( format!( "Was unable to write the netrc file: {:?}", netrc_fpath ), err ));
+    }
+
+    Ok(())
+}
+
+// Reads the password for 'set' from the first line of stdin.
+//
+fn read_password_stdin() -> Result<Secret, CliError> {
+
+    // The line is wrapped before it is checked, so that its buffer is zeroed
+    // (on drop) in any case; it is truncated in place, rather than copied.
+    let mut line = String::new();
+    let read = io::stdin().lock().read_line( &mut line );
+    let len = line.trim_end_matches( &['\n', '\r'][..] ).len();
+    line.truncate( len );
+    let password = Secret::new( line );
+
+    read.map_err(|err| CliError::IoErrorW( "Was unable to read the password from stdin".to_string(), err ))?;
+
+    if password.expose_secret().is_empty() {
+        return Err( CliError::Msg( "no password read from stdin; bailing out".to_string() ));
+    }
+
+    Ok( password )
+}
+
+// Edits the netrc file in place ('set', 'remove' and 'rename-host').
+//
+// Returns NoMatchingNetrcRecord* errors when there is no record to remove or
+// rename.
+//
+fn edit_netrc_file( cfg: &Config ) -> Result<(), CliError> {
+
+    let netrc_fpaths = netrc_file_paths( cfg )?;
+    if netrc_fpaths.len() != 1 {
+        return Err( CliError::Msg( format!( "editing requires a single netrc file, but {} are listed; use --netrc-file=FILE to choose one",
+                                            netrc_fpaths.len() )));
+    }
+
+    // Edit the file that a symlink points to (e.g., one in a dotfiles
+    // repository), rather than replacing the link with a regular file.
+    let mut netrc_fpath = netrc_fpaths[0].clone();
+    if fs::symlink_metadata( &netrc_fpath ).map(|md| md.file_type().is_symlink()).unwrap_or(false) {
+        netrc_fpath = fs::canonicalize( &netrc_fpath )
+            .map_err(|err| CliError::IoErrorW( format!( "Was unable to resolve the symlink: {:?}", netrc_fpath ), err ))?;
+        pr_debug!("netrc file is a symlink to: {:?}", netrc_fpath);
+    }
+
+    // Writing the edited file back would mean writing its plaintext to disk.
+    if decrypt_setting( cfg ).command_for( &netrc_fpath ).is_some() {
+        return Err( CliError::Msg( format!( "refusing to edit the encrypted netrc file {:?}; decrypt it, edit it, and encrypt it again instead",
+                                            netrc_fpath )));
+    }
+
+    let exists = netrc_fpath.exists();
+
+    let mut doc = if exists {
+        if !netrc_fpath.is_file() {
+            return Err( CliError::NetrcNotAFile{ path: netrc_fpath } );
+        }
+        if let Some(reason) = netrc_file_perms_problem( &netrc_fpath )? {
+            pr_warning!( "netrc file {:?}: {}; it will be rewritten with mode 0600", netrc_fpath, reason );
+        }
+        let netrc_file = File::open( &netrc_fpath )
+            .map_err(|err| CliError::IoErrorW( format!( "Was unable to open the netrc file: {:?}", netrc_fpath ), err ))?;
+        Document::parse( BufReader::new( netrc_file ))
+            .map_err(|err| netrc_error( &netrc_fpath, err ))?
+    }
+    else if let Mode::Set{..} = cfg.mode {
+        pr_info!("netrc file {:?} does not exist; it will be created", netrc_fpath);
+        Document::default()
+    }
+    else {
+        return Err( CliError::NetrcFileNotFound{ paths: vec![ netrc_fpath ] } );
+    };
+
+    match cfg.mode {
+        Mode::Set{ ref hostname, ref login } => {
+            let password = read_password_stdin()?;
+            if doc.set( hostname, login, Some( password.expose_secret() )) {
+                pr_info!("updated the password of the record for machine \"{}\", login \"{}\"", hostname, login);
+            }
+            else {
+                pr_info!("added a record for machine \"{}\", login \"{}\"", hostname, login);
+            }
+        },
+        Mode::Remove{ ref hostname } => {
+            let removed = doc.remove( hostname, cfg.username.as_deref() );
+            if 0 == removed {
+                if let Some(ref wanted_username) = cfg.username {
+                    return Err( CliError::NoMatchingNetrcRecord2{ hostname: hostname.clone(),
+                                                                  username: wanted_username.clone() } );
+                }
+                return Err( CliError::NoMatchingNetrcRecord1{ hostname: hostname.clone() } );
+            }
+            pr_info!("removed {} record(s) for machine \"{}\"", removed, hostname);
+        },
+        Mode::RenameHost{ ref old_hostname, ref new_hostname } => {
+            let renamed = doc.rename_host( old_hostname, new_hostname );
+            if 0 == renamed {
+                return Err( CliError::NoMatchingNetrcRecord1{ hostname: old_hostname.clone() } );
+            }
+            pr_info!("renamed {} record(s) for machine \"{}\" to \"{}\"", renamed, old_hostname, new_hostname);
+        },
+        _ => unreachable!( "not an editing mode: {:?}", cfg.mode ),
+    }
+
+    // The new content holds every password in the file.
+    let content = Secret::new( doc.to_string() );
+    write_netrc_file( &netrc_fpath, content.expose_secret(), exists )
+}
+
+
+// Finds the netrc record with which to answer git for 'url' (see
+// credential::find()): a 'machine' record for its host and port, or for
+// "api.github.com" in place of "github.com". The 'default' record is used
+// only with '--helper-default' (and not '--no-default'). The query is
+// narrowed by the '-u' (if 'url' has no user name), '--account' and
+// '--index' options.
+//
+fn find_for_git<'a>( cfg: &Config, loaded: &'a Loaded, url: &Url ) -> Result<Match<'a>, lookup::Error> {
+
+    let use_default = cfg.use_default && cfg.helper_default;
+    credential::find( loaded, url, use_default, |mut query| {
+        if let (None, Some(ref wanted_username)) = (&url.username, &cfg.username) {
+            query = query.user( wanted_username.clone() );
+        }
+        select_account( cfg, query )
+    })
+}
+
+// Implements the 'askpass PROMPT' subcommand, for use as git's GIT_ASKPASS
+// (or ssh's SSH_ASKPASS) program: prints the login or password asked for by
+// PROMPT (see the 'askpass' module), from the netrc record for the host, port
+// and user named in it, found as for the credential helper (see
+// find_for_git()).
+//
+// Nothing is ever read from stdin. An unrecognized prompt (e.g., for an ssh
+// key passphrase) is an error, as is a record with no password for a
+// password prompt; with no matching record, the usual no-match error results.
+// Either way nothing is printed, and git (or ssh) fails at once, rather than
+// falling back to prompting on the terminal.
+//
+fn run_askpass( cfg: &Config, prompt_text: &str ) -> Result<(), CliError> {
+
+    let prompt = Prompt::parse( prompt_text )
+        .ok_or_else(|| CliError::Msg( format!( "unrecognized askpass prompt: \"{}\"", prompt_text.trim_end() )))?;
+    pr_debug!( "askpass prompt for {:?}, for URL: {:?}", prompt.wanted, prompt.url );
+
+    let loaded = load_netrc_files( cfg )?;
+
+    let found = find_for_git( cfg, &loaded, &prompt.url )?;
+    note_match( &found );
+
+    let machine = match prompt.wanted {
+        Wanted::Username => Cow::Borrowed( found.machine ),
+        Wanted::Password => with_password( cfg, found.machine )?,
+    };
+    let answer = match prompt.wanted {
+        Wanted::Username => &machine.login[..],
+        Wanted::Password => match machine.password {
+            Some(ref password) => password.expose_secret(),
+            None => return Err( CliError::Msg( format!( "matched netrc record (in {:?}, line {}) has no password",
+                                                        found.file, found.line() ))),
+        },
+    };
+
+    // The answer is read as a single line.
+    if answer.contains( '\n' ) || answer.contains( '\r' ) {
+        return Err( CliError::Msg( format!( "matched netrc record (in {:?}, line {}) has a value with a line break, which cannot be an askpass answer",
+                                            found.file, found.line() )));
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    writeln!( out, "{}", answer )?;
+
+    Ok(())
+}
+
+// Implements the 'credential' subcommand, which allows the program to act as
+// a git credential helper. See gitcredentials(7).
+//
+// For 'get', the record is found for the URL made of the 'protocol' and 'host'
+// (with any port) attributes of the request, and the 'username' attribute, if
+// provided (see find_for_git()); a 'username' given with '-u' is used if the
+// request does not include one. When no record matches, nothing is emitted,
+// which tells git to try other helpers or prompt the user.
+//
+// The netrc file is never modified, so 'store' and 'erase' requests are read
+// and then ignored.
+//
+fn run_credential_helper( cfg: &Config, op: CredentialOp ) -> Result<(), CliError> {
+
+    let stdin = io::stdin();
+    let request = credential::Request::read( stdin.lock() )
+        .map_err(|err| CliError::IoErrorW( "Was unable to read credential request from stdin".to_string(), err ))?;
+    pr_trace!( "credential request: {:?}", request );  // never holds a password
+
+    if CredentialOp::Get != op {
+        pr_debug!( "netrc file is read-only for us; ignoring credential '{:?}' request", op );
+        return Ok(());
+    }
+
+    if request.host.is_none() {
+        pr_debug!( "credential request has no 'host' attribute; nothing to do" );
+        return Ok(());
+    }
+    let url = request.url()
+        .map_err(|msg| CliError::Msg( format!( "invalid credential request: {}", msg )))?;
+
+    pr_debug!( "credential request for URL: {:?} (path: {:?})", url, request.path );
+
+    let loaded = load_netrc_files( cfg )?;
+
+    let found = match find_for_git( cfg, &loaded, &url ) {
+        Ok(found) => found,
+        Err(_) => {
+            pr_info!( "no matching netrc record found for credential request for host: \"{}\"", url.host );
+            return Ok(());
+        }
+    };
+    note_match( &found );
+    let machine = with_password( cfg, found.machine )?;
+
+    // The credential protocol does not allow for values that contain a
+    // newline or NUL byte.
+    //
+    let is_unsafe = |val: &str| val.contains('\n') || val.contains('\0');
+    if is_unsafe( &machine.login )
+        || machine.password.as_ref().map_or( false, |pw| is_unsafe( pw.expose_secret() ))
+    {
+        return Err( CliError::Msg( format!( "matched netrc record (in {:?}) has a login or password that cannot be represented in the git credential protocol; bailing out",
+                                            found.file )));
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    writeln!( out, "username={}", machine.login )?;
+    if let Some(ref password) = machine.password {
+        writeln!( out, "password={}", password.expose_secret() )?;
+    }
+
+    Ok(())
+}
+
+// Implements the '--batch' option: reads queries from stdin (see the 'batch'
+// module) and answers each on stdout, in order. The netrc file(s) are read
+// once, up front; stdout is flushed after each answer, so that another program
+// can use us as a co-process.
+//
+// A query that matches no record (or that cannot be parsed) is answered with
+// a 'not-found' (or 'error') status; only a failure to read the netrc files,
+// stdin, or to write stdout, ends the run early.
+//
+fn run_batch( cfg: &Config ) -> Result<(), CliError> {
+
+    let loaded = load_netrc_files( cfg )?;
+
+    let json = OutputFormat::Json == cfg.output_format;
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    let mut lnum: usize = 0;
+    let mut buf: Vec<u8> = Vec::new();
+
+    loop {
+        buf.clear();
+        if 0 == input.read_until( b'\n', &mut buf )
+            .map_err(|err| CliError::IoErrorW( "Was unable to read a query from stdin".to_string(), err ))?
+        {
+            break;
+        }
+        lnum += 1;
+
+        let query = match std::str::from_utf8( &buf ) {
+            Ok(line) => {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                batch::parse_query( line )
+            },
+            Err(_) => Err( "query is not valid UTF-8".to_string() ),
+        };
+
+        let BatchQuery { host, user } = match query {
+            Ok(query) => query,
+            Err(msg) => {
+                pr_debug!( "stdin line {}: {}", lnum, msg );
+                if json {
+                    writeln!( out, "{{\"status\":\"error\",\"line\":{},\"message\":{}}}", lnum, json_quote( &msg ))?;
+                }
+                else {
+                    writeln!( out, "error\tline {}: {}", lnum, msg )?;
+                }
+                out.flush()?;
+                continue;
+            },
+        };
+
+        let user = user.or_else(|| cfg.username.clone());
+        let found = host_query( cfg, &host, user.as_deref() ).find( &loaded );
+
+        let query_json = || format!( "\"host\":{},\"user\":{}", json_quote( &host ),
+                                     user.as_deref().map_or( "null".to_string(), json_quote ));
+
+        match found {
+            Ok(found) => {
+                note_match( &found );
+                let machine = match machine_for_output( cfg, found.machine ) {
+                    Ok(machine) => machine,
+                    Err(err) => {
+                        pr_info!( "stdin line {}: {}", lnum, err );
+                        if json {
+                            writeln!( out, "{{\"status\":\"error\",\"line\":{},\"message\":{}}}", lnum, json_quote( &format!( "{}", err )))?;
+                        }
+                        else {
+                            writeln!( out, "error\tline {}: {}", lnum, err )?;
+                        }
+                        out.flush()?;
+                        continue;
+                    },
+                };
+                if json {
+                    writeln!( out, "{{\"status\":\"found\",{},\"record\":{}}}",
+                              query_json(), record_json( cfg, found.hostname, &machine ).expose_secret() )?;
+                    out.flush()?;
+                    continue;
+                }
+                let values: Vec<Secret> = cfg.fields.iter()
+                    .map(|field| field_value( *field, found.hostname, &machine ))
+                    .collect();
+                if values.iter().any(|val| val.expose_secret().contains( &['\t', '\n', '\r'][..] )) {
+                    writeln!( out, "error\tline {}: a field of the matched record contains a tab or newline; use -O json", lnum )?;
+                }
+                else {
+                    write!( out, "found\t" )?;
+                    write_tab_separated( &mut out, &values )?;
+                }
+            },
+            Err(_) if json => writeln!( out, "{{\"status\":\"not-found\",{}}}", query_json() )?,
+            Err(err) => {
+                pr_info!( "{}", err );
+                writeln!( out, "not-found" )?;
+            },
+        }
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+// Implements the '--url=URL' option: prints the record for the host and port
+// of URL, falling back to the 'default' record (unless --no-default).
+//
+fn lookup_url( cfg: &Config, wanted_url: &Url ) -> Result<(), CliError> {
+
+    let mut query = Query::url( wanted_url ).default_fallback( cfg.use_default );
+    if let Some(ref wanted_username) = cfg.username {
+        query = query.user( wanted_username.clone() );
+    }
+    let query = select_account( cfg, query );
+    pr_debug!( "searching for host \"{}\" (normalized), port {:?}",
+               url::normalize_host( &wanted_url.host ), wanted_url.effective_port() );
+
+    if let Some(ref sources) = cfg.sources {
+        return resolve_credential( cfg, sources, &query );
+    }
+
+    let loaded = load_netrc_files( cfg )?;
+
+    if cfg.all {
+        return print_all_records( cfg, &query, &loaded );
+    }
+
+    let found = query.find( &loaded )?;
+    note_match( &found );
+
+    print_record( cfg, found.hostname, found.machine )
+}
+
+// Implements the '--sources=LIST' option: prints the credentials for 'query'
+// from the first of 'sources' that has them (see the 'sources' module), in
+// the same output formats as a netrc record. The source (and where within
+// it the credentials were found) is noted with -v, and is included in the
+// JSON object as the "source" and "origin" members.
+//
+fn resolve_credential( cfg: &Config, sources: &[Source], query: &Query ) -> Result<(), CliError> {
+
+    pr_debug!( "credential sources to check, in order: {:?}", sources );
+
+    let mut resolver = Resolver::new( sources.to_vec() );
+    if sources.contains( &Source::Netrc ) {
+        match netrc_source( cfg ) {
+            Ok(source) => resolver = resolver.netrc_source( source ),
+            Err(CliError::NoHomeDir) => pr_debug!( "no home directory; the netrc source will be skipped" ),
+            Err(err) => return Err( err ),
+        }
+    }
+
+    let found: Credential = resolver.resolve( query )?;
+    for warning in &found.warnings {
+        pr_warning!( "{}", warning );
+    }
+    pr_info!( "credentials found by source '{}': {}", found.source, found.origin );
+
+    if OutputFormat::Json == cfg.output_format {
+        let machine = machine_for_output( cfg, &found.machine )?;
+        let record = record_json( cfg, found.hostname.as_deref(), &machine );
+        let record = record.expose_secret();
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        writeln!( out, "{},\"source\":{},\"origin\":{}}}",
+                  &record[..record.len() - 1], json_quote( found.source.name() ), json_quote( &found.origin ))?;
+        return Ok(());
+    }
+
+    print_record( cfg, found.hostname.as_deref(), &found.machine )
+}
+
+fn run_app( args: &[String] ) -> Result<(), CliError> {
+
+    pr_trace!("entered: run_app()");
+
+    let cfg: Config = match parse_cli_args( args ) {
+
+        Err(err)  => return Err(err),
+
+        Ok( CliSuccess::ProcessingIsComplete )  => return Ok(()),  // option already handled (e.g., --help)
+
+        // still have work to do here
+        Ok( CliSuccess::AdditionalProcessingRequired( config ) ) => *config,
+    };
+
+    pr_debug!("Successfully parsed command line options");
+
+    // The hostname for the "wanted" entry from the ~/.netrc file (assuming
+    // such an entry exists).
+    //
+    let wanted_hostname = match cfg.mode {
+        Mode::Lookup{ ref hostname } => hostname,
+        Mode::UrlLookup{ ref url } => return lookup_url( &cfg, url ),
+        Mode::Credential( op ) => return run_credential_helper( &cfg, op ),
+        Mode::Askpass{ ref prompt } => return run_askpass( &cfg, prompt ),
+        Mode::List => return list_records( &cfg, None ),
+        Mode::Batch => return run_batch( &cfg ),
+        Mode::Show{ ref hostname } => return list_records( &cfg, Some( hostname )),
+        Mode::Lint{ ref files } => return lint_files( &cfg, files ),
+        Mode::ListMacros => return print_macros( &cfg, None ),
+        Mode::Macro{ ref name } => return print_macros( &cfg, Some( name )),
+        Mode::Set{..} | Mode::Remove{..} | Mode::RenameHost{..} => return edit_netrc_file( &cfg ),
+    };
+    pr_debug!( "wanted hostname: {}", wanted_hostname );
+
+    if let Some(ref vv) = cfg.username {
+        pr_debug!( "wanted username: {}", vv );
+    }
+    else {
+        pr_debug!( "wanted username: [none provided (okay)]" );
+    }
+
+    let query = host_query( &cfg, wanted_hostname, cfg.username.as_deref() );
+
+    if let Some(ref sources) = cfg.sources {
+        return resolve_credential( &cfg, sources, &query );
+    }
+
+    let loaded = load_netrc_files( &cfg )?;
+
+    if cfg.all {
+        return print_all_records( &cfg, &query, &loaded );
+    }
+
+    // When none of the netrc records match the specified hostname (or
+    // hostname, username pair), the NoMatch error becomes one of our
+    // NoMatchingNetrcRecord* errors.
+    let found = query.find( &loaded )?;
+    note_match( &found );
+
+    print_record( &cfg, found.hostname, found.machine )
+}
+
 fn main() {
 
     // FIXME: maybe use OsString, instead, to allow for data in busted encoding on input
     //
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+
+    // When run by a name that ends in "-askpass" (e.g., via a symlink), act
+    // as 'askpass PROMPT', since git and ssh pass an askpass program nothing
+    // but the prompt.
+    if args.first().map( Path::new ).and_then( Path::file_name )
+        .and_then(|name| name.to_str()).map_or( false, |name| name.ends_with( "-askpass" ))
+    {
+        args.insert( 1, "askpass".to_string() );
+    }
+
+    // '--porcelain' (or an abbreviation of it that the option parser would
+    // accept) is honored even for an error in an option ahead of it.
+    let is_porcelain = |arg: &String| {
+        let name = arg.find( '=' ).map_or( &arg[..], |pos| &arg[..pos] );
+        arg.starts_with( "--" ) && getopt::find_long( OPTIONS, name ).map_or( false, |spec| Opt::Porcelain == spec.id )
+    };
+    if args.iter().skip(1).take_while(|arg| "--" != *arg).any( is_porcelain ) {
+        PORCELAIN_set( true );
+    }
 
     process::exit( match run_app( &args ) {
         Ok(_) => 0,      // success (match found)
         Err(err) => {
             match err {
+                _ if PORCELAIN() => {
+                    // A single line, for the benefit of the calling program
+                    eprintln!( "{}\t{}", err.failure().porcelain_code(), err.to_string().replace( '\n', " " ));
+                },
+
                 CliError::BadArgs(_) => {
                     pr_error!( "{}", err );
                     // The user provided invalid command line parameters, so we
@@ -658,7 +2718,12 @@ fn main() {
                     // message already printed above.
                     //
                     print_help( &mut io::stderr() ).unwrap();
-                    2  // error
+                },
+
+                  CliError::NoMatchingMacro{..}
+                | CliError::LintProblemsFound{..}  // the diagnostics are on stdout
+                  => {
+                      pr_info!( "{}", err );
                 },
 
                   CliError::NoMatchingNetrcRecord1{..}
@@ -673,16 +2738,47 @@ fn main() {
                       // enough to get that behavior, if desired.
                       //
                       pr_info!( "{}", err );  // yes, info -- that's not a typo
-
-                      1  // no match found
                 },
 
                 _ => {
                       pr_error!( "{}", err );
-                      2  // error
                 }
             }
 
+            err.failure().exit_status()
         }
     });
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Scripts (such as ads-github-cache) act on the exit status, or on the
+    // '--porcelain' error code, of each of these errors.
+    //
+    #[test]
+    fn error_statuses_and_codes() {
+        let io_err = || io::Error::new( io::ErrorKind::Other, "oops" );
+        let path = || PathBuf::from( "netrc" );
+        let cases: Vec<(CliError, i32, &str)> = vec![
+            (CliError::NoMatchingNetrcRecord1{ hostname: "h".to_string() },                          1, "no-match"),
+            (CliError::NoMatchingNetrcRecord2{ hostname: "h".to_string(), username: "u".to_string() }, 1, "no-match"),
+            (CliError::NoMatchingMacro{ name: "m".to_string() },                                      1, "no-macro"),
+            (CliError::LintProblemsFound{ count: 2 },                                                 1, "lint-problems"),
+            (CliError::BadArgs( "bad".to_string() ),                                                  2, "usage"),
+            (CliError::Msg( "msg".to_string() ),                                                      2, "error"),
+            (CliError::InsecureNetrcFile{ path: path(), reason: "mode 0644".to_string() },            3, "insecure-perms"),
+            (CliError::NetrcFileNotFound{ paths: vec![ path() ] },                                    4, "netrc-not-found"),
+            (CliError::NetrcNotAFile{ path: path() },                                                 5, "netrc-not-a-file"),
+            (CliError::NetrcParseError{ path: path(), lnum: 1, msg: "bad".to_string() },              6, "netrc-parse"),
+            (CliError::NoHomeDir,                                                                     7, "no-home"),
+            (CliError::IoError( io_err() ),                                                           8, "io"),
+            (CliError::IoErrorW( "reading".to_string(), io_err() ),                                   8, "io"),
+            (CliError::NetrcDecryptError{ path: path(), msg: "bad".to_string() },                     9, "netrc-decrypt"),
+        ];
+        for (err, status, code) in cases {
+            assert_eq!( (err.failure().exit_status(), err.failure().porcelain_code()), (status, code), "{:?}", err );
+        }
+    }
+}
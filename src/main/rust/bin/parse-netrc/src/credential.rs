@@ -0,0 +1,238 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Answering git (as a credential helper, or an askpass program) from netrc
+//! records.
+//!
+//! A [`Request`] is read in the git credential helper protocol (see
+//! gitcredentials(7) and git-credential(1)), and turned into the URL of the
+//! remote. The record for a URL, whether from a request or from an askpass
+//! prompt (see the [`askpass`](crate::askpass) module), is found by [`find`].
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::credential::{self, Request};
+//! use parse_netrc::lookup::NetrcSource;
+//!
+//! let netrc = "machine api.github.com login me password ghp_s3cret\ndefault login anonymous password guest\n";
+//! let loaded = NetrcSource::reader("netrc", netrc.as_bytes()).load().unwrap();
+//!
+//! let request = Request::read("protocol=https\nhost=github.com\n\n".as_bytes()).unwrap();
+//! let url = request.url().unwrap();
+//! let found = credential::find(&loaded, &url, false, |query| query).unwrap();
+//! assert_eq!(found.hostname, Some("api.github.com"));
+//!
+//! // No 'default' record for just any host.
+//! let request = Request::read("protocol=https\nhost=example.com\n".as_bytes()).unwrap();
+//! assert!(credential::find(&loaded, &request.url().unwrap(), false, |query| query).is_err());
+//! ```
+
+use std::io::{self, BufRead};
+
+use crate::lookup::{Error, Loaded, Match, Query};
+use crate::secret::Secret;
+use crate::url::{self, Url};
+
+/// A request from git to a credential helper. Only the attributes that
+/// matter for a netrc lookup are kept; the value of any `password`
+/// attribute is discarded as it is read. An attribute given with an empty
+/// value is unset, as in git.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Request {
+    /// The `protocol` attribute (e.g., "https").
+    pub protocol: Option<String>,
+
+    /// The `host` attribute: the host name, followed by the port
+    /// (":8443"), if it is not the default port of the protocol.
+    pub host: Option<String>,
+
+    /// The `path` attribute (sent only if git is configured with
+    /// `credential.useHttpPath`). netrc records have no path, so it plays no
+    /// part in a lookup.
+    pub path: Option<String>,
+
+    /// The `username` attribute.
+    pub username: Option<String>,
+}
+
+impl Request {
+    /// Reads a request from `input`: "key=value" lines, up to a blank line
+    /// or the end of the input. Lines that are not of that form, and
+    /// attributes other than those of [`Request`], are ignored. A later
+    /// value of an attribute replaces an earlier one.
+    pub fn read<R: BufRead>(mut input: R) -> io::Result<Request> {
+        let mut request = Request::default();
+        loop {
+            // Each line is wrapped, so that it is zeroed on drop: it may be
+            // a 'password' attribute.
+            let mut buf = String::new();
+            let read = input.read_line(&mut buf);
+            let line = Secret::new(buf);
+            if 0 == read? {
+                break;
+            }
+            let line = line.expose_secret().trim_end_matches('\n').trim_end_matches('\r');
+            if line.is_empty() {
+                break;
+            }
+            let (key, value) = match line.find('=') {
+                Some(pos) => (&line[..pos], &line[pos + 1..]),
+                None => continue,
+            };
+            let attr = match key {
+                "protocol" => &mut request.protocol,
+                "host"     => &mut request.host,
+                "path"     => &mut request.path,
+                "username" => &mut request.username,
+                _ => continue,
+            };
+            *attr = if value.is_empty() { None } else { Some(value.to_string()) };
+        }
+        Ok(request)
+    }
+
+    /// The URL of the remote: its protocol (by default, "https"), host and
+    /// port, and user name. The error is a message that describes the
+    /// problem.
+    pub fn url(&self) -> Result<Url, String> {
+        let host = self.host.as_deref().ok_or_else(|| "the request has no \"host\"".to_string())?;
+        if host.contains(&['/', '?', '#', '@'][..]) {
+            return Err(format!("invalid host: \"{}\"", host));
+        }
+        let protocol = self.protocol.as_deref().unwrap_or("https");
+        let mut url = Url::parse(&format!("{}://{}/", protocol, host))?;
+        url.username = self.username.clone();
+        Ok(url)
+    }
+}
+
+/// Finds the netrc record with which to answer git for `url` (the remote of
+/// a credential request, or of an askpass prompt). `narrow` adds any other
+/// selectors (an account, an index, ...) to the query.
+///
+/// Only a `machine` record matches, as for [`Query::url`]: by host (compared
+/// in normalized form) and port. With none for "github.com", the record for
+/// "api.github.com" (the host of the GitHub API) is used. Only if neither is
+/// found, and `use_default` is true, is the `default` record used: a
+/// password for any host is not one to hand to git for just any remote.
+pub fn find<'a, F>(loaded: &'a Loaded, url: &Url, use_default: bool, narrow: F) -> Result<Match<'a>, Error>
+where
+    F: Fn(Query) -> Query,
+{
+    let query = |url: &Url| narrow(Query::url(url).default_fallback(false));
+
+    if let Some(found) = query(url).find_machine(loaded) {
+        return Ok(found);
+    }
+    if "github.com" == url::normalize_host(&url.host) {
+        let api_url = Url { host: "api.github.com".to_string(), ..url.clone() };
+        if let Some(found) = query(&api_url).find_machine(loaded) {
+            return Ok(found);
+        }
+    }
+    query(url).default_fallback(use_default).find(loaded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lookup::NetrcSource;
+
+    fn request(s: &str) -> Request {
+        Request::read(s.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn read_requests() {
+        let req = request("protocol=https\nhost=github.com\npath=me/repo.git\nusername=me\npassword=s3cret\n\nhost=later\n");
+        assert_eq!(req, Request {
+            protocol: Some("https".to_string()),
+            host: Some("github.com".to_string()),
+            path: Some("me/repo.git".to_string()),
+            username: Some("me".to_string()),
+        });
+
+        // CRLF, no blank line, lines with no '=', unknown attributes, values
+        // with '=', and later and empty values.
+        let req = request("protocol=http\r\nbogus\r\nwwwauth[]=Basic realm=\"x\"\r\nhost=a:8080\r\nhost=b=c\r\nusername=me\r\nusername=\r\n");
+        assert_eq!(req, Request {
+            protocol: Some("http".to_string()),
+            host: Some("b=c".to_string()),
+            path: None,
+            username: None,
+        });
+
+        assert_eq!(request(""), Request::default());
+        assert_eq!(request("\nhost=ignored\n"), Request::default());
+        assert!(Request::read(&b"host=\xff\n"[..]).is_err());
+    }
+
+    #[test]
+    fn request_urls() {
+        let url = |s: &str| request(s).url();
+        let parts = |s: &str| {
+            let url = url(s).unwrap();
+            (url.scheme, url.host, url.port, url.username)
+        };
+        assert_eq!(parts("host=github.com\n"), ("https".to_string(), "github.com".to_string(), None, None));
+        assert_eq!(parts("protocol=http\nhost=ghe.corp:8080\nusername=a:b@c\n"),
+                   ("http".to_string(), "ghe.corp".to_string(), Some(8080), Some("a:b@c".to_string())));
+        assert_eq!(parts("protocol=https\nhost=[::1]:8443\n"), ("https".to_string(), "[::1]".to_string(), Some(8443), None));
+
+        assert!(url("protocol=https\n").is_err());
+        assert!(url("host=evil.com/x@github.com\n").is_err());
+        assert!(url("host=ghe.corp:http\n").is_err());
+        assert!(url("protocol=not a scheme\nhost=ghe.corp\n").is_err());
+    }
+
+    #[test]
+    fn find_records() {
+        let loaded = NetrcSource::reader("test", "\
+machine api.github.com login me password ghp_api
+machine ghe.corp login plain password p1
+machine ghe.corp login tls password p2 port 443
+machine ghe.corp login alt password p3 port 8443
+default login anonymous password guest
+".as_bytes()).load().unwrap();
+        let login = |req: &str, use_default: bool| {
+            let url = request(req).url().unwrap();
+            find(&loaded, &url, use_default, |query| query).ok().map(|m| m.machine.login.clone())
+        };
+
+        // "github.com" falls back to "api.github.com", not to 'default'.
+        assert_eq!(login("protocol=https\nhost=github.com\n", false), Some("me".to_string()));
+        assert_eq!(login("protocol=https\nhost=GitHub.COM\n", true), Some("me".to_string()));
+
+        // A foreign host gets nothing, unless the 'default' record is allowed.
+        assert_eq!(login("protocol=https\nhost=nothere.com\n", false), None);
+        assert_eq!(login("protocol=https\nhost=nothere.com\n", true), Some("anonymous".to_string()));
+        assert_eq!(login("protocol=https\nhost=github.com\nusername=other\n", false), None);
+
+        // The port (explicit, or that of the protocol) is matched: a record
+        // for another port is never used, and one with no port is used only
+        // if there is none for the port.
+        assert_eq!(login("protocol=https\nhost=ghe.corp\n", false), Some("tls".to_string()));
+        assert_eq!(login("protocol=https\nhost=ghe.corp:8443\n", false), Some("alt".to_string()));
+        assert_eq!(login("protocol=http\nhost=ghe.corp\n", false), Some("plain".to_string()));
+        assert_eq!(login("protocol=https\nhost=ghe.corp:9443\n", false), Some("plain".to_string()));
+        let url = request("protocol=http\nhost=ghe.corp\n").url().unwrap();
+        assert!(find(&loaded, &url, true, |query| query.user("tls")).is_err());
+    }
+}
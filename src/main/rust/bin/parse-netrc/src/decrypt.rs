@@ -0,0 +1,222 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Decrypting encrypted netrc files (such as the `~/.authinfo.gpg` of Emacs
+//! users), so that they can be read like any other.
+//!
+//! The file is fed to a decryption command on its stdin, and the plaintext
+//! is read from the command's stdout into memory (a [`SecretBytes`], which
+//! is zeroed on drop); it is never written to disk. The command is run as a
+//! `password_cmd` is (see the [`password_cmd`](crate::password_cmd) module):
+//! not by a shell, and killed if it does not finish within a timeout.
+//!
+//! By default ([`Decrypt::Auto`]), a file whose name ends in `.gpg` or
+//! `.asc` is decrypted with [`GPG_COMMAND`]; a gpg-agent may prompt for the
+//! passphrase of the key with its pinentry program.
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::decrypt::{Decrypt, GPG_COMMAND};
+//! use std::path::Path;
+//!
+//! assert_eq!(Decrypt::Auto.command_for(Path::new("/home/me/.authinfo.gpg")), Some(GPG_COMMAND));
+//! assert_eq!(Decrypt::Auto.command_for(Path::new("/home/me/.netrc")), None);
+//! ```
+
+use std::fs::File;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::password_cmd::{self, Error};
+use crate::secret::SecretBytes;
+
+/// The command with which `.gpg` and `.asc` files are decrypted.
+pub const GPG_COMMAND: &str = "gpg --batch --quiet --decrypt";
+
+/// How long a decryption command is given to finish. This is longer than for
+/// a `password_cmd`, to allow time to type a passphrase into a pinentry.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Which netrc files are decrypted, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decrypt {
+    /// Files whose names end in `.gpg` or `.asc` are decrypted with
+    /// [`GPG_COMMAND`]; others are read as is. This is the default.
+    Auto,
+
+    /// Every file is decrypted with the given command (e.g., from the
+    /// `--decrypt-with` option), which reads the file on its stdin and
+    /// writes the plaintext on its stdout.
+    With(String),
+}
+
+impl Default for Decrypt {
+    fn default() -> Decrypt {
+        Decrypt::Auto
+    }
+}
+
+impl Decrypt {
+    /// The command with which the file at `path` is to be decrypted, or None
+    /// if it is to be read as is.
+    pub fn command_for(&self, path: &Path) -> Option<&str> {
+        match self {
+            Decrypt::With(cmd) => Some(cmd),
+            Decrypt::Auto if is_encrypted_name(path) => Some(GPG_COMMAND),
+            Decrypt::Auto => None,
+        }
+    }
+}
+
+/// Whether `path` is named like an encrypted file: `*.gpg` or `*.asc`
+/// (ignoring case).
+pub fn is_encrypted_name(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("gpg") || ext.eq_ignore_ascii_case("asc"))
+}
+
+/// Decrypts `file` with `cmd`, killing the command if it has not finished
+/// within `timeout`, and returns the plaintext.
+pub fn decrypt(cmd: &str, file: File, timeout: Duration) -> Result<SecretBytes, Error> {
+    password_cmd::capture(cmd, Stdio::from(file), timeout)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lookup::{NetrcSource, PermsCheck, Query};
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn command_for_names() {
+        let with = Decrypt::With("age -d".to_string());
+        for &(name, auto) in &[(".netrc.gpg", true), (".authinfo.GPG", true), ("netrc.asc", true),
+                             (".netrc", false), ("gpg", false), (".gpg.d/netrc", false)] {
+            assert_eq!(Decrypt::Auto.command_for(Path::new(name)).is_some(), auto, "{}", name);
+            assert_eq!(with.command_for(Path::new(name)), Some("age -d"));
+        }
+    }
+
+    // A throwaway GnuPG home directory with a generated key (with no
+    // passphrase), removed (and its gpg-agent stopped) on drop.
+    struct GpgHome {
+        dir: PathBuf,
+    }
+
+    impl GpgHome {
+        // None if gpg is not installed, or could not generate a key.
+        fn new() -> Option<GpgHome> {
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos();
+            let dir = std::env::temp_dir().join(format!("pn-gpg-{}-{}", std::process::id(), nanos));
+            fs::create_dir(&dir).unwrap();
+            let home = GpgHome { dir };
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&home.dir, fs::Permissions::from_mode(0o700)).unwrap();
+            }
+            let generated = home.gpg()
+                .args(&["--passphrase", "", "--quick-generate-key", "Test <test@example.invalid>", "default", "default", "never"])
+                .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+                .status();
+            match generated {
+                Ok(status) if status.success() => Some(home),
+                _ => None,
+            }
+        }
+
+        fn gpg(&self) -> Command {
+            let mut gpg = Command::new("gpg");
+            gpg.arg("--homedir").arg(&self.dir).args(&["--batch", "--pinentry-mode", "loopback"]);
+            gpg
+        }
+
+        // Writes 'plaintext', encrypted to the test key (ASCII-armored, if
+        // 'armor'), to the file 'name'. The plaintext is fed to gpg through
+        // a pipe, so it is not on disk here, either.
+        fn encrypt(&self, name: &str, plaintext: &str, armor: bool) -> PathBuf {
+            let path = self.dir.join(name);
+            let mut gpg = self.gpg();
+            gpg.args(&["--trust-model", "always", "--recipient", "test@example.invalid", "--output"]).arg(&path);
+            if armor {
+                gpg.arg("--armor");
+            }
+            let mut child = gpg.arg("--encrypt").stdin(Stdio::piped()).spawn().unwrap();
+            child.stdin.take().unwrap().write_all(plaintext.as_bytes()).unwrap();
+            assert!(child.wait().unwrap().success());
+            path
+        }
+
+        fn decrypt_command(&self) -> String {
+            format!("gpg --homedir '{}' --batch --quiet --decrypt", self.dir.display())
+        }
+    }
+
+    impl Drop for GpgHome {
+        fn drop(&mut self) {
+            let _ = Command::new("gpgconf").arg("--homedir").arg(&self.dir).args(&["--kill", "gpg-agent"])
+                .stdout(Stdio::null()).stderr(Stdio::null()).status();
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn decrypt_gpg_files() {
+        let home = match GpgHome::new() {
+            Some(home) => home,
+            None => {
+                eprintln!("gpg is not available; skipping");
+                return;
+            }
+        };
+
+        let plaintext = "machine api.github.com login me password ghp_s3cret\r\ndefault login anon\r\n";
+        for &(name, armor) in &[("netrc.gpg", false), ("authinfo.asc", true)] {
+            let path = home.encrypt(name, plaintext, armor);
+            assert!(!fs::read(&path).unwrap().windows(10).any(|w| w == b"ghp_s3cret"));
+
+            let decrypted = decrypt(&home.decrypt_command(), File::open(&path).unwrap(), DEFAULT_TIMEOUT).unwrap();
+            assert_eq!(decrypted.expose_secret(), plaintext.as_bytes());
+
+            let loaded = NetrcSource::file(&path)
+                .perms_check(PermsCheck::Off)
+                .decrypt(Decrypt::With(home.decrypt_command()))
+                .load().unwrap();
+            let found = Query::host("api.github.com").find(&loaded).unwrap();
+            assert_eq!(found.machine.password.as_ref().unwrap(), "ghp_s3cret");
+            assert_eq!(found.file, path);
+        }
+
+        // Not encrypted (to this key, or at all).
+        let plain = home.dir.join("netrc");
+        fs::write(&plain, plaintext).unwrap();
+        let err = NetrcSource::file(&plain)
+            .perms_check(PermsCheck::Off)
+            .decrypt(Decrypt::With(home.decrypt_command()))
+            .load().unwrap_err();
+        assert!(format!("{}", err).starts_with("unable to decrypt"), "{}", err);
+    }
+}
@@ -0,0 +1,618 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! A lossless model of a netrc file, for editing it in place.
+//!
+//! The `Netrc` struct produced by `Netrc::parse` keeps only the values of the
+//! records, so it cannot be written back out without losing the comments,
+//! layout and quoting of the original. A `Document` instead keeps every byte
+//! of its input: each token exactly as written, and everything in between
+//! (whitespace, comments, `macdef` bodies) as "trivia". Writing out an
+//! unmodified `Document` reproduces its input exactly, and an edit touches
+//! only the tokens involved.
+//!
+//! As a `Document` holds every password in the file as it was written, its
+//! `Debug` output redacts the value of each `password` token, its text is
+//! zeroed when it is dropped, and the buffers in which the file is read are
+//! zeroed once it has been parsed (see the [`secret`](crate::secret) module).
+
+use std::fmt;
+use std::io::BufRead;
+use std::mem;
+
+use crate::netrc::{is_blank_line, Error, Netrc, Result, Tokens};
+use crate::secret::{self, Secret, SecretBytes};
+
+#[derive(Debug, Clone)]
+enum Item {
+    // Whitespace, comments and macro bodies, verbatim.
+    Trivia(String),
+
+    // A token: 'raw' is the text as it appears in the file (including any
+    // quotes), 'value' is what it means.
+    Token { raw: String, value: String },
+}
+
+// The positions (indexes into Document::items) of a 'machine' or 'default'
+// record and its interesting tokens. The record runs from its keyword up to
+// (but not including) the keyword that starts the next record or 'macdef'.
+struct RecordSpan {
+    start: usize,
+    end: usize,
+    hostname: Option<usize>,  // None for 'default'
+    login: Option<usize>,
+    password: Option<usize>,
+}
+
+/// A netrc file, as written. See the module-level docs.
+#[derive(Clone, Default)]
+pub struct Document {
+    items: Vec<Item>,
+}
+
+// Returns 'value' in a form that reads back as the same token: as-is when
+// possible, and otherwise double-quoted with backslash escapes.
+fn quote_token(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.starts_with('"')
+        || value.starts_with('#')
+        || value.chars().any(|c| c.is_whitespace());
+    if !needs_quotes {
+        return value.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"'  => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _    => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn token(value: &str) -> Item {
+    Item::Token { raw: quote_token(value), value: value.to_string() }
+}
+
+// Splits 'text' into lines, each with its '\n' (if any). This is what
+// str::split_inclusive('\n') does, but that is newer than the oldest Rust
+// that we support.
+fn lines_with_endings(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let end = rest.find('\n').map_or(rest.len(), |nl| nl + 1);
+        let (line, tail) = rest.split_at(end);
+        rest = tail;
+        Some(line)
+    })
+}
+
+impl Document {
+    /// Reads a `Document` from `buf`.
+    ///
+    /// Input that `Netrc::parse` would reject is rejected here, too (with the
+    /// same error), so the structure of a `Document` is never in doubt. So is
+    /// input that is not valid UTF-8 (even in a comment), as a `Document`
+    /// could not reproduce it exactly.
+    pub fn parse<A: BufRead>(mut buf: A) -> Result<Document> {
+        // The input is wrapped before it is checked, so that it is zeroed
+        // (on return) in any case; the text is borrowed from it, not copied.
+        let mut bytes = Vec::new();
+        let read = buf.read_to_end(&mut bytes);
+        let bytes = SecretBytes::new(bytes);
+        read.map_err(Error::Io)?;
+        let bytes = bytes.expose_secret();
+        Netrc::parse(bytes)?;
+        let text = std::str::from_utf8(bytes).map_err(|err| {
+            let valid = &bytes[..err.valid_up_to()];
+            let lnum = valid.iter().filter(|&&b| b'\n' == b).count() + 1;
+            Error::Parse("File is not valid UTF-8, so it cannot be edited".to_string(), lnum)
+        })?;
+
+        fn push_trivia(items: &mut Vec<Item>, s: &str) {
+            if s.is_empty() {
+                return;
+            }
+            match items.last_mut() {
+                Some(Item::Trivia(ref mut t)) => t.push_str(s),
+                _ => items.push(Item::Trivia(s.to_string())),
+            }
+        }
+
+        let mut items: Vec<Item> = Vec::new();
+
+        // A byte order mark is kept, but is not part of the first token.
+        let body = if text.starts_with('\u{feff}') {
+            push_trivia(&mut items, "\u{feff}");
+            &text['\u{feff}'.len_utf8()..]
+        }
+        else {
+            text
+        };
+
+        // These track the same state as the parser: whether the next token
+        // is the value of a keyword, and whether we are in a macro body.
+        let mut expect_value = false;
+        let mut prev_keyword = String::new();
+        let mut in_macdef_body = false;
+
+        for (lidx, line) in lines_with_endings(body).enumerate() {
+            if in_macdef_body {
+                push_trivia(&mut items, line);
+                if is_blank_line(line) {
+                    in_macdef_body = false;
+                }
+                continue;
+            }
+
+            let mut toks = Tokens::new(line.to_string());
+            let mut prev_end = 0;
+            loop {
+                let value = match toks.next() {
+                    None           => break,
+                    Some(Ok(v))    => v,
+                    Some(Err(msg)) => return Err(Error::Parse(msg, lidx + 1)),
+                };
+                push_trivia(&mut items, &line[prev_end..toks.start]);
+                items.push(Item::Token { raw: line[toks.start..toks.cur].to_string(), value: value.clone() });
+                prev_end = toks.cur;
+
+                if expect_value {
+                    expect_value = false;
+                    if prev_keyword == "macdef" {
+                        // The rest of the line begins the macro body.
+                        in_macdef_body = true;
+                        break;
+                    }
+                }
+                else if value != "default" {
+                    expect_value = true;
+                    prev_keyword = value;
+                }
+            }
+            push_trivia(&mut items, &line[prev_end..]);
+        }
+
+        Ok(Document { items })
+    }
+
+    // Locates the 'machine' and 'default' records, in file order.
+    fn records(&self) -> Vec<RecordSpan> {
+        let mut spans: Vec<RecordSpan> = Vec::new();
+        let mut current: Option<RecordSpan> = None;
+        let mut keyword: Option<&str> = None;
+
+        for (idx, item) in self.items.iter().enumerate() {
+            let value = match item {
+                Item::Token { value, .. } => &value[..],
+                Item::Trivia(_)           => continue,
+            };
+
+            if let Some(kw) = keyword.take() {
+                if let Some(ref mut rec) = current {
+                    match kw {
+                        "machine"  => rec.hostname = Some(idx),
+                        "login"    => rec.login = Some(idx),
+                        "password" => rec.password = Some(idx),
+                        _          => (),
+                    }
+                }
+                continue;
+            }
+
+            if let "machine" | "default" | "macdef" = value {
+                if let Some(mut rec) = current.take() {
+                    rec.end = idx;
+                    spans.push(rec);
+                }
+                if value != "macdef" {
+                    current = Some(RecordSpan { start: idx, end: idx, hostname: None, login: None, password: None });
+                }
+            }
+            if value != "default" {
+                keyword = Some(value);
+            }
+        }
+        if let Some(mut rec) = current.take() {
+            rec.end = self.items.len();
+            spans.push(rec);
+        }
+        spans
+    }
+
+    fn value_at(&self, idx: Option<usize>) -> Option<&str> {
+        match idx.map(|i| &self.items[i]) {
+            Some(Item::Token { value, .. }) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn set_value_at(&mut self, idx: usize, value: &str) {
+        self.items[idx] = token(value);
+    }
+
+    // Whether 'rec' is a 'machine' record for 'hostname'. As in a lookup by
+    // host (see lookup::Query::host()), ASCII case is ignored.
+    fn is_record_for(&self, rec: &RecordSpan, hostname: &str) -> bool {
+        self.value_at(rec.hostname).map_or(false, |name| name.eq_ignore_ascii_case(hostname))
+    }
+
+    /// Sets the password of the first record for `hostname` (ignoring ASCII
+    /// case, as a lookup does) whose login is `login`, adding a `password`
+    /// entry to it if necessary. If there is no such record, one is added
+    /// (ahead of any `default` record, which should remain last).
+    ///
+    /// Returns true if an existing record was updated.
+    pub fn set(&mut self, hostname: &str, login: &str, password: Option<&str>) -> bool {
+        let records = self.records();
+
+        let found = records.iter().find(|rec| {
+            self.is_record_for(rec, hostname) && self.value_at(rec.login) == Some(login)
+        });
+
+        if let Some(rec) = found {
+            match (password, rec.password, rec.login) {
+                (None, _, _) => (),
+                (Some(password), Some(idx), _) => self.set_value_at(idx, password),
+                (Some(password), None, Some(login_idx)) => {
+                    let new_items = vec![Item::Trivia(" ".to_string()), token("password"),
+                                         Item::Trivia(" ".to_string()), token(password)];
+                    self.items.splice(login_idx + 1..login_idx + 1, new_items);
+                }
+                (Some(_), None, None) => unreachable!("matched record has a login"),
+            }
+            return true;
+        }
+
+        let mut new_items = vec![token("machine"), Item::Trivia(" ".to_string()), token(hostname),
+                                 Item::Trivia(" ".to_string()), token("login"),
+                                 Item::Trivia(" ".to_string()), token(login)];
+        if let Some(password) = password {
+            new_items.extend(vec![Item::Trivia(" ".to_string()), token("password"),
+                                  Item::Trivia(" ".to_string()), token(password)]);
+        }
+        new_items.push(Item::Trivia("\n".to_string()));
+
+        match records.iter().find(|rec| rec.hostname.is_none()) {
+            Some(default_rec) => {
+                self.items.splice(default_rec.start..default_rec.start, new_items);
+            }
+            None => {
+                let text = self.to_string();
+                let mut sep = String::new();
+                if !text.is_empty() && !text.ends_with('\n') {
+                    sep.push('\n');
+                }
+                if self.ends_in_open_macdef() {
+                    // End the macro body, lest it swallow the new record.
+                    sep.push('\n');
+                }
+                if !sep.is_empty() {
+                    self.items.push(Item::Trivia(sep));
+                }
+                self.items.extend(new_items);
+            }
+        }
+        false
+    }
+
+    // Whether the document ends within a 'macdef' body that is not
+    // terminated by a blank line.
+    fn ends_in_open_macdef(&self) -> bool {
+        let last_macdef = self.items.iter().rposition(|item| match item {
+            Item::Token { value, .. } => value == "macdef",
+            Item::Trivia(_) => false,
+        });
+        let last_record = self.records().last().map(|rec| rec.start);
+        let body_start = match (last_macdef, last_record) {
+            (Some(m), Some(r)) if r > m => return false,
+            (Some(m), _) => (m + 1..self.items.len())  // skip the macro name
+                .find(|&idx| matches!(self.items[idx], Item::Token { .. }))
+                .map_or(self.items.len(), |idx| idx + 1),
+            (None, _)    => return false,
+        };
+        let body = &self.items[body_start..];
+        let body: String = body.iter().map(|item| match item {
+            Item::Trivia(t) | Item::Token { raw: t, .. } => &t[..],
+        }).collect();
+        let terminated = lines_with_endings(&body).skip(1).any(is_blank_line);
+        !terminated
+    }
+
+    /// Removes the records for `hostname` (ignoring ASCII case; only those
+    /// whose login is `login`, if given), along with any comments on the same
+    /// lines.
+    ///
+    /// Returns the number of records removed.
+    pub fn remove(&mut self, hostname: &str, login: Option<&str>) -> usize {
+        let records = self.records();
+        let mut removed = 0;
+
+        for rec in records.iter().rev() {
+            if !self.is_record_for(rec, hostname) {
+                continue;
+            }
+            if login.is_some() && self.value_at(rec.login) != login {
+                continue;
+            }
+
+            let last_token = (rec.start..rec.end).rev()
+                .find(|&idx| matches!(self.items[idx], Item::Token { .. }))
+                .unwrap_or(rec.start);
+            let trailing: String = self.items[last_token + 1..rec.end].iter().map(|item| match item {
+                Item::Trivia(t) | Item::Token { raw: t, .. } => &t[..],
+            }).collect();
+
+            let before: String = self.items[..rec.start].iter().map(|item| match item {
+                Item::Trivia(t) | Item::Token { raw: t, .. } => &t[..],
+            }).collect();
+            let before_trimmed = before.trim_end_matches(&[' ', '\t'][..]);
+            let at_line_start = before_trimmed.is_empty() || before_trimmed.ends_with('\n');
+
+            // A record on a line of its own takes its line with it. One that
+            // shares a line with something else leaves the line break.
+            let keep = if at_line_start {
+                trailing.find('\n').map_or("", |nl| &trailing[nl + 1..]).to_string()
+            }
+            else {
+                trailing
+            };
+
+            if rec.start > 0 {
+                if let Item::Trivia(ref mut t) = self.items[rec.start - 1] {
+                    let len = t.trim_end_matches(&[' ', '\t'][..]).len();
+                    t.truncate(len);
+                }
+            }
+            let replacement = if keep.is_empty() { vec![] } else { vec![Item::Trivia(keep)] };
+            self.items.splice(rec.start..rec.end, replacement);
+            removed += 1;
+        }
+
+        // Coalesce any adjacent runs of trivia left behind.
+        let mut items: Vec<Item> = Vec::with_capacity(self.items.len());
+        for item in self.items.drain(..) {
+            match (items.last_mut(), item) {
+                (Some(Item::Trivia(ref mut t)), Item::Trivia(more)) => t.push_str(&more),
+                (_, item) => items.push(item),
+            }
+        }
+        items.retain(|item| !matches!(item, Item::Trivia(t) if t.is_empty()));
+        self.items = items;
+
+        removed
+    }
+
+    /// Changes the host name of every `machine` record for `old_hostname`
+    /// (ignoring ASCII case) to `new_hostname`.
+    ///
+    /// Returns the number of records changed.
+    pub fn rename_host(&mut self, old_hostname: &str, new_hostname: &str) -> usize {
+        let targets: Vec<usize> = self.records().iter()
+            .filter(|rec| self.is_record_for(rec, old_hostname))
+            .filter_map(|rec| rec.hostname)
+            .collect();
+        for &idx in &targets {
+            self.set_value_at(idx, new_hostname);
+        }
+        targets.len()
+    }
+}
+
+// An item as shown by the Debug impl of Document: redacted, if it is the
+// value of a 'password' token.
+struct Shown<'a> {
+    item: &'a Item,
+    redact: bool,
+}
+
+impl<'a> fmt::Debug for Shown<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.item {
+            Item::Token { .. } if self.redact => f.debug_struct("Token")
+                .field("raw", &secret::REDACTED)
+                .field("value", &secret::REDACTED)
+                .finish(),
+            item => item.fmt(f),
+        }
+    }
+}
+
+/// Shows the items of the document, with the value of each `password` token
+/// redacted.
+impl fmt::Debug for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // As in records(): a token that is not the value of a keyword is a
+        // keyword ('default' takes no value).
+        let mut keyword: Option<&str> = None;
+        let mut items: Vec<Shown> = Vec::with_capacity(self.items.len());
+        for item in &self.items {
+            let mut redact = false;
+            if let Item::Token { value, .. } = item {
+                let of_keyword = keyword.take();
+                if of_keyword.is_none() && value != "default" {
+                    keyword = Some(value);
+                }
+                redact = Some("password") == of_keyword;
+            }
+            items.push(Shown { item, redact });
+        }
+        f.debug_struct("Document").field("items", &items).finish()
+    }
+}
+
+impl Drop for Document {
+    fn drop(&mut self) {
+        // Each string is wrapped, and so zeroed, as it is dropped.
+        for item in &mut self.items {
+            match item {
+                Item::Trivia(t) => drop(Secret::new(mem::take(t))),
+                Item::Token { raw, value } => {
+                    drop(Secret::new(mem::take(raw)));
+                    drop(Secret::new(mem::take(value)));
+                }
+            }
+        }
+    }
+}
+
+/// Writes the document out as netrc text.
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for item in &self.items {
+            match item {
+                Item::Trivia(t) | Item::Token { raw: t, .. } => f.write_str(t)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::secret::Secret;
+
+    const INPUT: &str = "\
+# work account
+machine example.com
+    login alice   # rotated quarterly
+    password \"s3 cr\\\"et\"
+
+macdef init
+cd /pub
+machine not-a-record
+
+machine other.example.com login bob password hunter2 port 8080
+default login anonymous password me@example.com
+";
+
+    fn doc(s: &str) -> Document {
+        Document::parse(s.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn round_trip_is_lossless() {
+        assert_eq!(doc(INPUT).to_string(), INPUT);
+        assert_eq!(doc("").to_string(), "");
+        assert_eq!(doc("machine a login b").to_string(), "machine a login b");
+    }
+
+    #[test]
+    fn parse_rejects_what_netrc_rejects() {
+        assert!(Document::parse("machine a bogus b".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn set_existing_password() {
+        let mut d = doc(INPUT);
+        assert!(d.set("example.com", "alice", Some("new token")));
+        assert_eq!(d.to_string(), INPUT.replace("\"s3 cr\\\"et\"", "\"new token\""));
+
+        let netrc = Netrc::parse(d.to_string().as_bytes()).unwrap();
+        assert_eq!(netrc.hosts[0].1.password, Some(Secret::from("new token")));
+    }
+
+    #[test]
+    fn set_rotates_token_of_differently_cased_host() {
+        let mut d = doc("machine github.com login me password old\n");
+        assert!(d.set("GitHub.com", "me", Some("new")));
+        assert_eq!(d.to_string(), "machine github.com login me password new\n");
+
+        // A login is still compared exactly.
+        assert!(!d.set("GITHUB.COM", "Me", Some("other")));
+        assert_eq!(d.to_string(), "machine github.com login me password new\nmachine GITHUB.COM login Me password other\n");
+    }
+
+    #[test]
+    fn set_adds_missing_password() {
+        let mut d = doc("machine a login b # note\n");
+        assert!(d.set("a", "b", Some("pw")));
+        assert_eq!(d.to_string(), "machine a login b password pw # note\n");
+    }
+
+    #[test]
+    fn set_new_record_goes_before_default() {
+        let mut d = doc(INPUT);
+        assert!(!d.set("new.example.com", "carol", Some("pw")));
+        assert_eq!(d.to_string(),
+                   INPUT.replace("default login", "machine new.example.com login carol password pw\ndefault login"));
+    }
+
+    #[test]
+    fn set_new_record_closes_open_macdef() {
+        let mut d = doc("macdef init\ncd /pub");
+        d.set("a", "b", Some("c"));
+        assert_eq!(d.to_string(), "macdef init\ncd /pub\n\nmachine a login b password c\n");
+
+        let netrc = Netrc::parse(d.to_string().as_bytes()).unwrap();
+        assert_eq!(netrc.hosts.len(), 1);
+    }
+
+    #[test]
+    fn set_new_record_after_crlf_macdef() {
+        let mut d = doc("macdef init\r\ncd /pub\r\n\r\n");
+        d.set("a", "b", Some("c"));
+        assert_eq!(d.to_string(), "macdef init\r\ncd /pub\r\n\r\nmachine a login b password c\n");
+    }
+
+    #[test]
+    fn remove_records() {
+        let mut d = doc(INPUT);
+        assert_eq!(d.remove("example.com", Some("nobody")), 0);
+        assert_eq!(d.remove("example.com", None), 1);
+        assert_eq!(d.to_string(), "\
+# work account
+
+macdef init
+cd /pub
+machine not-a-record
+
+machine other.example.com login bob password hunter2 port 8080
+default login anonymous password me@example.com
+");
+
+        let mut d = doc("machine a login b machine c login d\nmachine e login f\n");
+        assert_eq!(d.remove("c", Some("d")), 1);
+        assert_eq!(d.to_string(), "machine a login b\nmachine e login f\n");
+
+        let mut d = doc("machine GitHub.com login me\nmachine github.com login bot\nmachine e login f\n");
+        assert_eq!(d.remove("github.COM", None), 2);
+        assert_eq!(d.to_string(), "machine e login f\n");
+    }
+
+    #[test]
+    fn rename_host() {
+        let mut d = doc("machine a login x\nmachine b login y\nmachine a login z\n");
+        assert_eq!(d.rename_host("a", "new host"), 2);
+        assert_eq!(d.to_string(), "machine \"new host\" login x\nmachine b login y\nmachine \"new host\" login z\n");
+
+        let mut d = doc("machine GHE.corp login x\nmachine ghe.corp login y\n");
+        assert_eq!(d.rename_host("ghe.CORP", "ghe.example.com"), 2);
+        assert_eq!(d.to_string(), "machine ghe.example.com login x\nmachine ghe.example.com login y\n");
+    }
+}
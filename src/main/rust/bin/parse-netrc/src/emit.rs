@@ -0,0 +1,153 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Renders a login and password as configuration for another program, so
+//! that the credentials can be handed to it through a pipe (never written to
+//! disk), rather than having it parse the netrc file itself.
+//!
+//! Each [`Emit`] format quotes the values as that program expects, and a
+//! value that cannot be represented in the format (e.g., a login with a `:`
+//! for curl, which splits "user:password" at the first colon) is an error,
+//! rather than something the program would misread.
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::emit::Emit;
+//!
+//! let config = Emit::CurlConfig.render("me", "s3\"cret").unwrap();
+//! assert_eq!(config.expose_secret(), "user = \"me:s3\\\"cret\"\n");
+//! ```
+
+use crate::secret::Secret;
+
+/// A configuration format for the credentials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// A curl config file (for `curl -K -`): `user = "LOGIN:PASSWORD"`.
+    CurlConfig,
+
+    /// An HTTP header (for `curl -H @-`): `Authorization: token PASSWORD`, as
+    /// for the GitHub API. The login is not used.
+    AuthHeader,
+
+    /// A wgetrc file (for `wget --config=FILE`): `user = LOGIN` and
+    /// `password = PASSWORD` lines.
+    WgetRc,
+}
+
+impl Emit {
+    /// The format named `name`: "curl-config", "auth-header" or "wget-rc".
+    pub fn from_name(name: &str) -> Option<Emit> {
+        match name {
+            "curl-config" => Some(Emit::CurlConfig),
+            "auth-header" => Some(Emit::AuthHeader),
+            "wget-rc"     => Some(Emit::WgetRc),
+            _             => None,
+        }
+    }
+
+    /// Renders `login` and `password` in this format, as one or more
+    /// newline-terminated lines. The error is a message that describes why
+    /// the values cannot be represented.
+    pub fn render(&self, login: &str, password: &str) -> Result<Secret, String> {
+        match self {
+            Emit::CurlConfig => {
+                if login.contains(':') {
+                    return Err(format!("login \"{}\" contains a ':', which curl would take to end it", login));
+                }
+                let user = Secret::new(format!("{}:{}", login, password));
+                Ok(Secret::new(format!("user = {}\n", curl_quote(user.expose_secret()).expose_secret())))
+            }
+            Emit::AuthHeader => {
+                if password.is_empty() || password.chars().any(|c| c.is_control() || ' ' == c) {
+                    return Err("the password is empty, or contains whitespace or control characters, \
+                                so it cannot be sent as a token in an HTTP header".to_string());
+                }
+                Ok(Secret::new(format!("Authorization: token {}\n", password)))
+            }
+            Emit::WgetRc => {
+                for &(name, value) in &[("login", login), ("password", password)] {
+                    // wget trims the value, and has no quoting or escapes.
+                    if value.trim() != value || value.chars().any(|c| '\n' == c || '\r' == c) {
+                        return Err(format!("the {} begins or ends with whitespace, or contains a line break, \
+                                            which a wgetrc file cannot represent", name));
+                    }
+                }
+                Ok(Secret::new(format!("user = {}\npassword = {}\n", login, password)))
+            }
+        }
+    }
+}
+
+// Returns 'value' as a double-quoted string of a curl config file, in which
+// a backslash escapes the character that follows it ('\t', '\n', '\r' and
+// '\v' are control characters). See the description of '-K' in curl(1).
+//
+// The value holds the password, so the result is wrapped, too, and has room
+// for every character to be escaped: the buffer is never grown (and so never
+// copied).
+fn curl_quote(value: &str) -> Secret {
+    let mut quoted = String::with_capacity(2 * value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"'    => quoted.push_str("\\\""),
+            '\\'   => quoted.push_str("\\\\"),
+            '\t'   => quoted.push_str("\\t"),
+            '\n'   => quoted.push_str("\\n"),
+            '\r'   => quoted.push_str("\\r"),
+            '\x0b' => quoted.push_str("\\v"),
+            _      => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Secret::new(quoted)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render_formats() {
+        let cases: &[(Emit, &str, &str, Result<&str, ()>)] = &[
+            (Emit::CurlConfig, "me", "ghp_x",         Ok("user = \"me:ghp_x\"\n")),
+            (Emit::CurlConfig, "me", "a b\"c\\d\te#", Ok("user = \"me:a b\\\"c\\\\d\\te#\"\n")),
+            (Emit::CurlConfig, "j\u{f6}rg", "p:w",    Ok("user = \"j\u{f6}rg:p:w\"\n")),
+            (Emit::CurlConfig, "m:e", "p",            Err(())),
+            (Emit::AuthHeader, "ignored", "ghp_x",    Ok("Authorization: token ghp_x\n")),
+            (Emit::AuthHeader, "me", "ghp_x\r\nX: y", Err(())),
+            (Emit::AuthHeader, "me", "a b",           Err(())),
+            (Emit::AuthHeader, "me", "",              Err(())),
+            (Emit::WgetRc,     "me", "p#w=\"x\"",     Ok("user = me\npassword = p#w=\"x\"\n")),
+            (Emit::WgetRc,     "me", " p",            Err(())),
+            (Emit::WgetRc,     "m\ne", "p",           Err(())),
+        ];
+        for (emit, login, password, expected) in cases {
+            let rendered = emit.render(login, password);
+            match expected {
+                Ok(text) => assert_eq!(rendered.unwrap().expose_secret(), *text, "{:?}", emit),
+                Err(())  => assert!(rendered.is_err(), "{:?} {:?} {:?}", emit, login, password),
+            }
+        }
+        assert_eq!(Emit::from_name("wget-rc"), Some(Emit::WgetRc));
+        assert_eq!(Emit::from_name("wgetrc"), None);
+    }
+}
@@ -0,0 +1,141 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! The classes of failure of the **`parse-netrc`** program, each with its
+//! exit status and its `--porcelain` error code.
+//!
+//! These are part of the interface of the program: scripts (such as
+//! `ads-github-cache`) act on the exit status, or on the error code, so
+//! neither may change once released. See the "Exit status" section of the
+//! help message.
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::failure::Failure;
+//!
+//! assert_eq!(Failure::NetrcNotFound.exit_status(), 4);
+//! assert_eq!(Failure::NetrcNotFound.porcelain_code(), "netrc-not-found");
+//! ```
+
+/// A class of failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// No matching netrc record was found.
+    NoMatch,
+
+    /// There is no netrc macro of the name given with `--macro`.
+    NoMacro,
+
+    /// `--lint` found problems.
+    LintProblems,
+
+    /// The command line arguments are invalid.
+    Usage,
+
+    /// Any error not otherwise classified.
+    Error,
+
+    /// A netrc file has unsafe permissions or ownership.
+    InsecurePerms,
+
+    /// The netrc file (or every one of a list of them) does not exist.
+    NetrcNotFound,
+
+    /// The netrc file is not a regular file.
+    NetrcNotAFile,
+
+    /// The netrc file could not be parsed.
+    NetrcParse,
+
+    /// The user's home directory could not be determined.
+    NoHome,
+
+    /// An I/O error occurred.
+    Io,
+
+    /// An encrypted netrc file could not be decrypted.
+    NetrcDecrypt,
+}
+
+impl Failure {
+    /// The exit status of the program.
+    pub fn exit_status(self) -> i32 {
+        match self {
+            Failure::NoMatch
+            | Failure::NoMacro
+            | Failure::LintProblems  => 1,
+            Failure::Usage
+            | Failure::Error         => 2,
+            Failure::InsecurePerms   => 3,
+            Failure::NetrcNotFound   => 4,
+            Failure::NetrcNotAFile   => 5,
+            Failure::NetrcParse      => 6,
+            Failure::NoHome          => 7,
+            Failure::Io              => 8,
+            Failure::NetrcDecrypt    => 9,
+        }
+    }
+
+    /// The error code printed on stderr with `--porcelain`.
+    pub fn porcelain_code(self) -> &'static str {
+        match self {
+            Failure::NoMatch       => "no-match",
+            Failure::NoMacro       => "no-macro",
+            Failure::LintProblems  => "lint-problems",
+            Failure::Usage         => "usage",
+            Failure::Error         => "error",
+            Failure::InsecurePerms => "insecure-perms",
+            Failure::NetrcNotFound => "netrc-not-found",
+            Failure::NetrcNotAFile => "netrc-not-a-file",
+            Failure::NetrcParse    => "netrc-parse",
+            Failure::NoHome        => "no-home",
+            Failure::Io            => "io",
+            Failure::NetrcDecrypt  => "netrc-decrypt",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // These values are relied upon by scripts; a change to this table is a
+    // change to the interface of the program.
+    #[test]
+    fn statuses_and_codes_are_stable() {
+        let cases: &[(Failure, i32, &str)] = &[
+            (Failure::NoMatch,       1, "no-match"),
+            (Failure::NoMacro,       1, "no-macro"),
+            (Failure::LintProblems,  1, "lint-problems"),
+            (Failure::Usage,         2, "usage"),
+            (Failure::Error,         2, "error"),
+            (Failure::InsecurePerms, 3, "insecure-perms"),
+            (Failure::NetrcNotFound, 4, "netrc-not-found"),
+            (Failure::NetrcNotAFile, 5, "netrc-not-a-file"),
+            (Failure::NetrcParse,    6, "netrc-parse"),
+            (Failure::NoHome,        7, "no-home"),
+            (Failure::Io,            8, "io"),
+            (Failure::NetrcDecrypt,  9, "netrc-decrypt"),
+        ];
+        for &(failure, status, code) in cases {
+            assert_eq!((failure.exit_status(), failure.porcelain_code()), (status, code), "{:?}", failure);
+        }
+    }
+}
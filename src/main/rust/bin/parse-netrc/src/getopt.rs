@@ -0,0 +1,451 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Command line option parsing that behaves like GNU `getopt_long(3)`, and so
+//! like the Bash programs of the ads-github-tools (which use GNU
+//! `getopt(1)`).
+//!
+//! * A long option's value may be attached with `=` (`--user=USER`) or given
+//!   as the next argument (`--user USER`).
+//! * Short options may be bundled (`-vvv`, `-vu USER`), and a short option's
+//!   value may be attached (`-uUSER`) or given as the next argument.
+//! * A long option may be abbreviated to any unambiguous prefix of its name
+//!   (`--verb` for `--verbose`); an exact match always wins.
+//! * Options and operands may be intermixed ("permutation"): options are
+//!   recognized anywhere on the command line unless POSIX behavior is asked
+//!   for (see [`Parser::posixly_correct`]), in which case the first operand
+//!   ends the options.
+//! * The argument `--` ends the options; everything after it is an operand.
+//!   A lone `-` is an operand (conventionally, stdin).
+//!
+//! The [`Parser`] is an iterator, so options are seen in command line order,
+//! and an option such as `--help` can be acted on before any error that
+//! follows it is reported.
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::getopt::{HasArg, Item, OptSpec, Parser};
+//!
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+//! enum Opt { User, Verbose }
+//!
+//! const OPTIONS: &[OptSpec<Opt>] = &[
+//!     OptSpec::new(Opt::User,    Some('u'), Some("user"),    HasArg::Required),
+//!     OptSpec::new(Opt::Verbose, Some('v'), Some("verbose"), HasArg::No),
+//! ];
+//!
+//! let args = ["-vv", "api.github.com", "--user=me"];
+//! let items: Vec<Item<Opt>> = Parser::new(&args, OPTIONS).collect::<Result<_, _>>().unwrap();
+//! assert_eq!(items, vec![
+//!     Item::Opt { id: Opt::Verbose, name: "-v".to_string(), value: None },
+//!     Item::Opt { id: Opt::Verbose, name: "-v".to_string(), value: None },
+//!     Item::Operand("api.github.com".to_string()),
+//!     Item::Opt { id: Opt::User, name: "--user".to_string(), value: Some("me".to_string()) },
+//! ]);
+//! ```
+
+use std::error;
+use std::fmt;
+
+/// Whether an option takes a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HasArg {
+    /// The option takes no value.
+    No,
+
+    /// The option requires a value, which may be the next argument.
+    Required,
+
+    /// The option takes a value only if it is attached (`--opt=VAL`, `-oVAL`).
+    Optional,
+}
+
+/// Describes one option: its identifier, its short and/or long names, and
+/// whether it takes a value.
+#[derive(Debug, Clone, Copy)]
+pub struct OptSpec<T> {
+    pub id: T,
+    pub short: Option<char>,
+    pub long: Option<&'static str>,
+    pub has_arg: HasArg,
+}
+
+impl<T> OptSpec<T> {
+    pub const fn new(id: T, short: Option<char>, long: Option<&'static str>, has_arg: HasArg) -> OptSpec<T> {
+        OptSpec { id, short, long, has_arg }
+    }
+}
+
+/// One parsed command line item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item<T> {
+    /// An option. The `name` is the option as given (`-u`, or the full name
+    /// of a long option, `--user`), for use in messages; the `value` is
+    /// always present for an option that requires one.
+    Opt { id: T, name: String, value: Option<String> },
+
+    /// An operand (a non-option argument).
+    Operand(String),
+
+    /// The `--` argument. Everything after it is an operand.
+    EndOfOpts,
+}
+
+/// A problem with the command line, described as GNU `getopt_long(3)` would.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// No long option has the given name (or a name that it abbreviates).
+    UnrecognizedOption(String),
+
+    /// No short option is the given character.
+    InvalidOption(char),
+
+    /// The abbreviated long option could be any of the `candidates`.
+    AmbiguousOption { given: String, candidates: Vec<String> },
+
+    /// The option requires a value, but none was given.
+    MissingArgument(String),
+
+    /// The long option takes no value, but one was attached with `=`.
+    UnexpectedArgument(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnrecognizedOption(given) =>
+                write!(f, "unrecognized option '{}'", given),
+            Error::InvalidOption(ch) =>
+                write!(f, "invalid option -- '{}'", ch),
+            Error::AmbiguousOption { given, candidates } => {
+                write!(f, "option '{}' is ambiguous; possibilities:", given)?;
+                for candidate in candidates {
+                    write!(f, " '{}'", candidate)?;
+                }
+                Ok(())
+            },
+            Error::MissingArgument(name) if name.starts_with("--") =>
+                write!(f, "option '{}' requires an argument", name),
+            Error::MissingArgument(name) =>
+                write!(f, "option requires an argument -- '{}'", &name[1..]),
+            Error::UnexpectedArgument(name) =>
+                write!(f, "option '{}' doesn't allow an argument", name),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Finds the spec of the long option `given` (e.g., `--verb`, without any
+/// `=VALUE`), as the [`Parser`] does: by its full name, or by an unambiguous
+/// prefix of it.
+pub fn find_long<'a, T>(specs: &'a [OptSpec<T>], given: &str) -> Result<&'a OptSpec<T>, Error> {
+    let name = if given.starts_with("--") { &given[2..] } else { given };
+    if let Some(spec) = specs.iter().find(|spec| spec.long == Some(name)) {
+        return Ok(spec);
+    }
+    let candidates: Vec<&OptSpec<T>> = specs.iter()
+        .filter(|spec| spec.long.map_or(false, |long| long.starts_with(name)))
+        .collect();
+    match candidates.len() {
+        0 => Err(Error::UnrecognizedOption(given.to_string())),
+        1 => Ok(candidates[0]),
+        _ => Err(Error::AmbiguousOption {
+            given: given.to_string(),
+            candidates: candidates.iter().map(|spec| format!("--{}", spec.long.unwrap_or(""))).collect(),
+        }),
+    }
+}
+
+/// An iterator over the [`Item`]s of a command line (without the program
+/// name). After an error, the iterator is exhausted.
+pub struct Parser<'a, T, S> {
+    args: &'a [S],
+    specs: &'a [OptSpec<T>],
+
+    // The index of the next argument to examine.
+    idx: usize,
+
+    // Within a bundle of short options, the argument and the byte offset of
+    // the next option character in it.
+    cluster: Option<(&'a str, usize)>,
+
+    // Set once "--" (or, with POSIX behavior, the first operand) is seen, or
+    // after an error.
+    done_with_opts: bool,
+    failed: bool,
+
+    posixly_correct: bool,
+    stop_at: Option<fn(&str) -> bool>,
+    seen_operand: bool,
+}
+
+impl<'a, T: Copy, S: AsRef<str>> Parser<'a, T, S> {
+    pub fn new(args: &'a [S], specs: &'a [OptSpec<T>]) -> Parser<'a, T, S> {
+        Parser {
+            args, specs, idx: 0, cluster: None, done_with_opts: false, failed: false,
+            posixly_correct: false, stop_at: None, seen_operand: false,
+        }
+    }
+
+    /// Sets whether the first operand ends the options, as with the
+    /// `POSIXLY_CORRECT` environment variable for GNU programs.
+    pub fn posixly_correct(mut self, posixly_correct: bool) -> Parser<'a, T, S> {
+        self.posixly_correct = posixly_correct;
+        self
+    }
+
+    /// Ends the options at the first operand (before any `--`) for which
+    /// `is_subcommand` is true, leaving the arguments that follow it (which
+    /// are all returned as operands) for the subcommand to parse.
+    pub fn stop_at(mut self, is_subcommand: fn(&str) -> bool) -> Parser<'a, T, S> {
+        self.stop_at = Some(is_subcommand);
+        self
+    }
+
+    fn fail(&mut self, err: Error) -> Option<Result<Item<T>, Error>> {
+        self.failed = true;
+        Some(Err(err))
+    }
+
+    fn next_arg(&mut self) -> Option<&'a str> {
+        let arg = self.args.get(self.idx).map(|arg| arg.as_ref());
+        if arg.is_some() {
+            self.idx += 1;
+        }
+        arg
+    }
+
+    fn long_opt(&mut self, arg: &'a str) -> Result<Item<T>, Error> {
+        let (given, attached) = match arg.find('=') {
+            Some(pos) => (&arg[..pos], Some(&arg[pos + 1..])),
+            None      => (arg, None),
+        };
+        let spec = find_long(self.specs, given)?;
+        let name = format!("--{}", spec.long.unwrap_or(""));
+        let value = match (spec.has_arg, attached) {
+            (HasArg::No, Some(_)) => return Err(Error::UnexpectedArgument(name)),
+            (HasArg::No, None) | (HasArg::Optional, None) => None,
+            (_, Some(value)) => Some(value.to_string()),
+            (HasArg::Required, None) => match self.next_arg() {
+                Some(value) => Some(value.to_string()),
+                None        => return Err(Error::MissingArgument(name)),
+            },
+        };
+        Ok(Item::Opt { id: spec.id, name, value })
+    }
+
+    fn short_opt(&mut self, arg: &'a str, pos: usize) -> Result<Item<T>, Error> {
+        let ch = arg[pos..].chars().next().unwrap_or('-');
+        let rest = pos + ch.len_utf8();
+        self.cluster = if rest < arg.len() { Some((arg, rest)) } else { None };
+
+        let spec = self.specs.iter().find(|spec| spec.short == Some(ch))
+            .ok_or(Error::InvalidOption(ch))?;
+        let name = format!("-{}", ch);
+        let value = match spec.has_arg {
+            HasArg::No => None,
+            _ if self.cluster.is_some() => {
+                self.cluster = None;
+                Some(arg[rest..].to_string())
+            },
+            HasArg::Optional => None,
+            HasArg::Required => match self.next_arg() {
+                Some(value) => Some(value.to_string()),
+                None        => return Err(Error::MissingArgument(name)),
+            },
+        };
+        Ok(Item::Opt { id: spec.id, name, value })
+    }
+}
+
+impl<'a, T: Copy, S: AsRef<str>> Iterator for Parser<'a, T, S> {
+    type Item = Result<Item<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        if let Some((arg, pos)) = self.cluster {
+            return match self.short_opt(arg, pos) {
+                Ok(item) => Some(Ok(item)),
+                Err(err) => self.fail(err),
+            };
+        }
+
+        let arg = self.next_arg()?;
+
+        if self.done_with_opts {
+            return Some(Ok(Item::Operand(arg.to_string())));
+        }
+
+        let result = if "--" == arg {
+            self.done_with_opts = true;
+            Ok(Item::EndOfOpts)
+        }
+        else if arg.starts_with("--") {
+            self.long_opt(arg)
+        }
+        else if arg.starts_with('-') && arg.len() > 1 {
+            self.short_opt(arg, 1)
+        }
+        else {
+            let is_subcommand = !self.seen_operand && self.stop_at.map_or(false, |stop_at| stop_at(arg));
+            self.seen_operand = true;
+            if self.posixly_correct || is_subcommand {
+                self.done_with_opts = true;
+            }
+            Ok(Item::Operand(arg.to_string()))
+        };
+
+        match result {
+            Ok(item) => Some(Ok(item)),
+            Err(err) => self.fail(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Opt { Field, List, Show, ShowPassword, User, Verbose, Color }
+
+    const OPTIONS: &[OptSpec<Opt>] = &[
+        OptSpec::new(Opt::Field,        Some('f'), Some("field"),         HasArg::Required),
+        OptSpec::new(Opt::List,         None,      Some("list"),          HasArg::No),
+        OptSpec::new(Opt::Show,         None,      Some("show"),          HasArg::Required),
+        OptSpec::new(Opt::ShowPassword, None,      Some("show-password"), HasArg::No),
+        OptSpec::new(Opt::User,         Some('u'), Some("user"),          HasArg::Required),
+        OptSpec::new(Opt::Verbose,      Some('v'), Some("verbose"),       HasArg::No),
+        OptSpec::new(Opt::Color,        Some('c'), Some("color"),         HasArg::Optional),
+    ];
+
+    // A compact rendering of the parsed items: "ID" or "ID=VALUE" for an
+    // option, the operand itself in brackets, and "--" for the end of the
+    // options. An error is rendered as "error: MESSAGE".
+    fn render(args: &[&str], posixly_correct: bool) -> Vec<String> {
+        Parser::new(args, OPTIONS)
+            .posixly_correct(posixly_correct)
+            .stop_at(|arg| "set" == arg)
+            .map(|item| match item {
+                Ok(Item::Opt { id, value: None, .. })        => format!("{:?}", id),
+                Ok(Item::Opt { id, value: Some(value), .. }) => format!("{:?}={}", id, value),
+                Ok(Item::Operand(operand))                   => format!("[{}]", operand),
+                Ok(Item::EndOfOpts)                          => "--".to_string(),
+                Err(err)                                     => format!("error: {}", err),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_forms() {
+        let cases: &[(&[&str], &[&str])] = &[
+            // Long options, with the value attached or separate.
+            (&["--user=me", "host"],     &["User=me", "[host]"]),
+            (&["--user", "me", "host"],  &["User=me", "[host]"]),
+            (&["--user=", "host"],       &["User=", "[host]"]),
+            (&["--user=a=b"],            &["User=a=b"]),
+            (&["--user", "-v"],          &["User=-v"]),
+
+            // Short options: attached value, separate value, bundling.
+            (&["-ume", "host"],          &["User=me", "[host]"]),
+            (&["-u", "me", "host"],      &["User=me", "[host]"]),
+            (&["-vvv"],                  &["Verbose", "Verbose", "Verbose"]),
+            (&["-vume"],                 &["Verbose", "User=me"]),
+            (&["-vu", "me"],             &["Verbose", "User=me"]),
+            (&["-uv"],                   &["User=v"]),
+
+            // Optional values must be attached.
+            (&["--color", "x"],          &["Color", "[x]"]),
+            (&["--color=auto", "-cnever", "-c"], &["Color=auto", "Color=never", "Color"]),
+
+            // Unambiguous abbreviations; an exact match wins.
+            (&["--verb", "--us=me"],     &["Verbose", "User=me"]),
+            (&["--show", "h"],           &["Show=h"]),
+            (&["--show-p"],              &["ShowPassword"]),
+
+            // Permutation, "--", and "-".
+            (&["host", "-v", "x"],       &["[host]", "Verbose", "[x]"]),
+            (&["-v", "--", "-u", "x"],   &["Verbose", "--", "[-u]", "[x]"]),
+            (&["-", "-v"],               &["[-]", "Verbose"]),
+
+            // A subcommand ends the options, but only as the first operand,
+            // and not after "--".
+            (&["-v", "set", "h", "-v"],  &["Verbose", "[set]", "[h]", "[-v]"]),
+            (&["h", "set", "-v"],        &["[h]", "[set]", "Verbose"]),
+            (&["--", "set", "-v"],       &["--", "[set]", "[-v]"]),
+
+            // Errors end the parse.
+            (&["--bogus", "-v"],         &["error: unrecognized option '--bogus'"]),
+            (&["-vx", "-v"],             &["Verbose", "error: invalid option -- 'x'"]),
+            (&["--sh"],                  &["error: option '--sh' is ambiguous; possibilities: '--show' '--show-password'"]),
+            (&["--list=yes"],            &["error: option '--list' doesn't allow an argument"]),
+            (&["--us"],                  &["error: option '--user' requires an argument"]),
+            (&["-v", "-u"],              &["Verbose", "error: option requires an argument -- 'u'"]),
+        ];
+
+        for (args, expected) in cases {
+            assert_eq!(render(args, false), *expected, "args: {:?}", args);
+        }
+    }
+
+    #[test]
+    fn parse_posixly_correct() {
+        let cases: &[(&[&str], &[&str])] = &[
+            (&["-v", "host", "-v"],      &["Verbose", "[host]", "[-v]"]),
+            (&["-v", "--", "host"],      &["Verbose", "--", "[host]"]),
+        ];
+
+        for (args, expected) in cases {
+            assert_eq!(render(args, true), *expected, "args: {:?}", args);
+        }
+    }
+
+    #[test]
+    fn find_long_options() {
+        let cases: &[(&str, Option<Opt>)] = &[
+            ("--show",          Some(Opt::Show)),
+            ("--show-p",        Some(Opt::ShowPassword)),
+            ("--verb",          Some(Opt::Verbose)),
+            ("--v",             Some(Opt::Verbose)),
+            ("--sh",            None),
+            ("--bogus",         None),
+            ("--",              None),
+        ];
+        for &(given, expected) in cases {
+            assert_eq!(find_long(OPTIONS, given).ok().map(|spec| spec.id), expected, "given: {:?}", given);
+        }
+    }
+
+    #[test]
+    fn option_names() {
+        let args = ["-f", "login", "--fi=port", "--field", "machine"];
+        let names: Vec<String> = Parser::new(&args, OPTIONS)
+            .map(|item| match item {
+                Ok(Item::Opt { name, .. }) => name,
+                other => panic!("unexpected item: {:?}", other),
+            })
+            .collect();
+        assert_eq!(names, ["-f", "--field", "--field"]);
+    }
+}
@@ -34,3 +34,76 @@
 #[macro_use]  // bld_date!(), bld_version!()
 #[path = "configure-time.rs"]
 pub mod configure_time;
+
+// Our in-tree copy of the (formerly vendored) third-party 'netrc' crate. See
+// the module-level docs for how it differs from the upstream version.
+//
+pub mod netrc;
+
+// GNU getopt_long(3)-style command line option parsing.
+//
+pub mod getopt;
+
+// A lossless model of a netrc file, for editing it in place ('set',
+// 'remove', 'rename-host').
+//
+pub mod document;
+
+// Reports all of the problems found in a netrc file ('--lint').
+//
+pub mod lint;
+
+// Just enough URL parsing to match a URL against netrc records ('--url').
+//
+pub mod url;
+
+// The public netrc lookup API: where the netrc data comes from, which record
+// is wanted, and where the record found came from.
+//
+pub mod lookup;
+
+// A wrapper for passwords and tokens that is redacted when formatted and
+// zeroed on drop.
+//
+pub mod secret;
+
+// Resolving credentials from a chain of sources: netrc files, environment
+// variables, and the files of 'gh' and git ('--sources').
+//
+pub mod sources;
+
+// Renders the credentials as curl, HTTP header or wget configuration
+// ('--emit').
+//
+pub mod emit;
+
+// Understands the prompts that git and ssh pass to an askpass program
+// ('askpass PROMPT').
+//
+pub mod askpass;
+
+// Runs the command of a 'password_cmd' entry (with '--allow-password-cmd').
+//
+pub mod password_cmd;
+
+// Decrypts encrypted (e.g., '.gpg') netrc files, in memory.
+//
+pub mod decrypt;
+
+// Quotes values for the JSON and shell output formats.
+//
+pub mod quote;
+
+// Answers git, as a credential helper or an askpass program, from netrc
+// records ('credential', 'askpass PROMPT').
+//
+pub mod credential;
+
+// The classes of failure of the program, with their exit statuses and
+// '--porcelain' error codes.
+//
+pub mod failure;
+
+// Parses the queries of the '--batch' mode.
+//
+pub mod batch;
@@ -0,0 +1,439 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Checks a netrc file for problems, reporting all of them (rather than just
+//! the first) with line and column positions.
+//!
+//! `Netrc::parse` gives up at the first thing it cannot make sense of, and
+//! happily accepts a number of things that are almost certainly mistakes
+//! (e.g., a record that can never be selected because an earlier record has
+//! the same host and login). The `lint` function here walks the same token
+//! stream as the parser, but keeps going after each problem it finds.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+
+use crate::netrc::{self, Lexer};
+use crate::password_cmd;
+use crate::secret::Secret;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Something that `Netrc::parse` (or curl) would reject outright.
+    Error,
+
+    /// Something that parses, but probably does not do what was intended.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error   => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// One problem found in a netrc file. The `lnum` and `col` fields are both
+/// 1-based; `col` counts characters (not bytes).
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub lnum: usize,
+    pub col: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+// The record ('machine' or 'default') currently being read.
+struct Record {
+    hostname: Option<String>,  // None for 'default'
+    lnum: usize,
+    col: usize,
+    login: Option<String>,
+    password: Option<Secret>,
+    password_cmd: Option<String>,
+    port: Option<String>,
+}
+
+impl Record {
+    fn describe(&self) -> String {
+        match self.hostname {
+            Some(ref h) => format!("'machine {}'", h),
+            None        => "'default'".to_string(),
+        }
+    }
+}
+
+struct Linter {
+    diags: Vec<Diagnostic>,
+    current: Option<Record>,
+
+    // (hostname, login, port) => line of the first record seen with them
+    seen: HashMap<(String, String, Option<String>), usize>,
+
+    // position of the first 'default' record, if any
+    default_at: Option<(usize, usize)>,
+    default_not_last_reported: bool,
+}
+
+impl Linter {
+    fn report(&mut self, lnum: usize, col: usize, severity: Severity, message: String) {
+        self.diags.push(Diagnostic { lnum, col, severity, message });
+    }
+
+    fn finish_record(&mut self) {
+        let rec = match self.current.take() {
+            Some(rec) => rec,
+            None      => return,
+        };
+
+        if rec.password.is_some() && rec.password_cmd.is_some() {
+            let msg = format!("{} record has both a 'password' and a 'password_cmd' (the 'password_cmd' is never run)",
+                              rec.describe());
+            self.report(rec.lnum, rec.col, Severity::Warning, msg);
+        }
+
+        let login = match rec.login {
+            Some(ref login) => login.clone(),
+            None => {
+                self.report(rec.lnum, rec.col, Severity::Warning,
+                            format!("{} record has no 'login'", rec.describe()));
+                String::new()
+            }
+        };
+
+        if let Some(ref hostname) = rec.hostname {
+            let key = (host_key(hostname), login.clone(), rec.port.clone());
+            match self.seen.get(&key) {
+                Some(&first_lnum) => {
+                    let msg = format!("'machine {}' record with login '{}' is shadowed by the record on line {}",
+                                      hostname, login, first_lnum);
+                    self.report(rec.lnum, rec.col, Severity::Warning, msg);
+                }
+                None => {
+                    self.seen.insert(key, rec.lnum);
+                }
+            }
+
+            if is_github_host(hostname) && rec.password_cmd.is_none()
+                && rec.password.as_ref().map_or(true, |pw| pw.expose_secret().is_empty())
+            {
+                let msg = format!("'machine {}' record has no 'password' (GitHub token)", hostname);
+                self.report(rec.lnum, rec.col, Severity::Warning, msg);
+            }
+        }
+    }
+}
+
+// Returns 'hostname' in the form in which lookups compare host names: in ASCII
+// lowercase, and without a trailing dot.
+fn host_key(hostname: &str) -> String {
+    hostname.trim_end_matches('.').to_ascii_lowercase()
+}
+
+fn is_github_host(hostname: &str) -> bool {
+    let h = host_key(hostname);
+    h == "github.com" || h.ends_with(".github.com")
+}
+
+/// Checks the netrc content in `buf`, returning every problem found, ordered
+/// by position. An empty result means the file is clean.
+///
+/// Only an I/O error reading `buf` is returned as an `Err`; anything wrong
+/// with the content itself is reported as a `Diagnostic`.
+///
+/// # Examples
+///
+/// ```
+/// use parse_netrc::lint::{lint, Severity};
+/// use std::io::Cursor;
+///
+/// let diags = lint(Cursor::new(b"machine example.com login foo pasword bar")).unwrap();
+/// assert_eq!(diags.len(), 1);
+/// assert_eq!(diags[0].severity, Severity::Error);
+/// assert_eq!((diags[0].lnum, diags[0].col), (1, 31));
+/// ```
+pub fn lint<A: BufRead>(buf: A) -> netrc::Result<Vec<Diagnostic>> {
+    let mut lexer = Lexer::new(buf);
+    let mut linter = Linter {
+        diags: Vec::new(),
+        current: None,
+        seen: HashMap::new(),
+        default_at: None,
+        default_not_last_reported: false,
+    };
+
+    // After an unknown keyword we do not know whether it was meant to take a
+    // value, so we stay quiet about further unknown tokens until we get back
+    // to a keyword we recognize.
+    let mut resyncing = false;
+
+    loop {
+        let tok = match lexer.next_token() {
+            None                           => break,
+            Some(Ok(tok))                  => tok,
+            Some(Err(netrc::Error::Io(e))) => return Err(netrc::Error::Io(e)),
+            Some(Err(netrc::Error::Parse(msg, lnum))) => {
+                let col = lexer.col();
+                linter.report(lnum, col, Severity::Error, msg);
+                continue;
+            }
+        };
+
+        // Reads the value that must follow the keyword in 'tok'.
+        macro_rules! value_for {
+            ($tok: expr) => {
+                match lexer.next_token() {
+                    Some(Ok(v)) => Some(v),
+                    Some(Err(netrc::Error::Io(e))) => return Err(netrc::Error::Io(e)),
+                    Some(Err(netrc::Error::Parse(msg, lnum))) => {
+                        let col = lexer.col();
+                        linter.report(lnum, col, Severity::Error, msg);
+                        None
+                    }
+                    None => {
+                        let msg = format!("'{}' at end of file with no value", $tok.text);
+                        linter.report($tok.lnum, $tok.col, Severity::Error, msg);
+                        None
+                    }
+                }
+            }
+        }
+
+        match &tok.text[..] {
+            "machine" => {
+                resyncing = false;
+                linter.finish_record();
+                if let (Some((d_lnum, d_col)), false) = (linter.default_at, linter.default_not_last_reported) {
+                    let msg = format!("'default' record is not the last record ('machine' entry follows on line {})",
+                                      tok.lnum);
+                    linter.report(d_lnum, d_col, Severity::Warning, msg);
+                    linter.default_not_last_reported = true;
+                }
+                if let Some(host) = value_for!(tok) {
+                    linter.current = Some(Record {
+                        hostname: Some(host.text),
+                        lnum: tok.lnum,
+                        col: tok.col,
+                        login: None,
+                        password: None,
+                        password_cmd: None,
+                        port: None,
+                    });
+                }
+            }
+            "default" => {
+                resyncing = false;
+                linter.finish_record();
+                match linter.default_at {
+                    Some((d_lnum, _)) => {
+                        let msg = format!("duplicate 'default' record (first seen on line {})", d_lnum);
+                        linter.report(tok.lnum, tok.col, Severity::Warning, msg);
+                    }
+                    None => linter.default_at = Some((tok.lnum, tok.col)),
+                }
+                linter.current = Some(Record {
+                    hostname: None,
+                    lnum: tok.lnum,
+                    col: tok.col,
+                    login: None,
+                    password: None,
+                    password_cmd: None,
+                    port: None,
+                });
+            }
+            "login" | "password" | "password_cmd" | "account" | "port" => {
+                resyncing = false;
+                let value = value_for!(tok);
+                if let ("password_cmd", Some(ref v)) = (&tok.text[..], &value) {
+                    if let Err(err) = password_cmd::split_words(&v.text) {
+                        linter.report(v.lnum, v.col, Severity::Error, format!("{}", err));
+                    }
+                }
+                match linter.current {
+                    None => {
+                        let msg = format!("'{}' is not inside a 'machine' or 'default' record", tok.text);
+                        linter.report(tok.lnum, tok.col, Severity::Error, msg);
+                    }
+                    Some(ref mut rec) => match (&tok.text[..], value) {
+                        (_, None) => (),
+                        ("login", Some(v))    => rec.login = Some(v.text),
+                        ("password", Some(v)) => rec.password = Some(Secret::new(v.text)),
+                        ("password_cmd", Some(v)) => rec.password_cmd = Some(v.text),
+                        ("port", Some(v)) => {
+                            rec.port = Some(v.text.clone());
+                            if v.text.parse::<u16>().is_err() {
+                                let msg = format!("unable to parse port number '{}'", v.text);
+                                linter.report(v.lnum, v.col, Severity::Error, msg);
+                            }
+                        }
+                        _ => (),
+                    },
+                }
+            }
+            "macdef" => {
+                resyncing = false;
+                linter.finish_record();
+                let name = match value_for!(tok) {
+                    Some(name) => name.text,
+                    None       => continue,
+                };
+                let body_lnum = lexer.lnum + 1;
+                let (body, terminated) = lexer.read_subcommands()?;
+
+                for (idx, line) in body.lines().enumerate() {
+                    let word = line.split_whitespace().next().unwrap_or("");
+                    if word == "machine" || word == "default" {
+                        let col = line.find(word).map_or(1, |off| line[..off].chars().count() + 1);
+                        let msg = format!("'{}' inside the body of macdef '{}' (a macro ends at the first blank line)",
+                                          word, name);
+                        linter.report(body_lnum + idx, col, Severity::Warning, msg);
+                    }
+                }
+                if !terminated {
+                    let msg = format!("macdef '{}' is not terminated by a blank line", name);
+                    linter.report(tok.lnum, tok.col, Severity::Warning, msg);
+                }
+            }
+            _ => {
+                if !resyncing {
+                    let msg = format!("unknown keyword '{}'", tok.text);
+                    linter.report(tok.lnum, tok.col, Severity::Error, msg);
+                    resyncing = true;
+                }
+            }
+        }
+    }
+    linter.finish_record();
+
+    let mut diags = linter.diags;
+    diags.sort_by_key(|d| (d.lnum, d.col));
+    Ok(diags)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    fn lint_str(s: &str) -> Vec<(usize, usize, Severity)> {
+        lint(Cursor::new(s.as_bytes())).unwrap()
+            .into_iter()
+            .map(|d| (d.lnum, d.col, d.severity))
+            .collect()
+    }
+
+    #[test]
+    fn lint_clean() {
+        let input = "machine example.com login u password p\n\
+                     default login anonymous password me@example.com\n";
+        assert_eq!(lint_str(input), vec![]);
+    }
+
+    #[test]
+    fn lint_keeps_going() {
+        let input = "machine a login u passwd p\n\
+                     \x20 port http\n\
+                     password x\n";
+        let diags = lint(Cursor::new(input.as_bytes())).unwrap();
+        assert_eq!(diags.len(), 2);
+        assert_eq!((diags[0].lnum, diags[0].col), (1, 19));
+        assert!(diags[0].message.contains("passwd"));
+
+        // 'p' is skipped along with the unknown keyword, and 'port' is seen
+        // as a keyword again
+        assert_eq!((diags[1].lnum, diags[1].col), (2, 8));
+        assert!(diags[1].message.contains("http"));
+    }
+
+    #[test]
+    fn lint_outside_record() {
+        assert_eq!(lint_str("password x\nmachine a login u\n"),
+                   vec![(1, 1, Severity::Error)]);
+    }
+
+    #[test]
+    fn lint_missing_login_and_value() {
+        assert_eq!(lint_str("machine a password p\nmachine b login"),
+                   vec![(1, 1, Severity::Warning),
+                        (2, 1, Severity::Warning),
+                        (2, 11, Severity::Error)]);
+    }
+
+    #[test]
+    fn lint_shadowed_and_default_not_last() {
+        let input = "machine a login u password p\n\
+                     default login anon\n\
+                     machine a login u password q\n";
+        let diags = lint(Cursor::new(input.as_bytes())).unwrap();
+        assert_eq!(diags.len(), 2);
+        assert_eq!((diags[0].lnum, diags[0].severity), (2, Severity::Warning));
+        assert!(diags[0].message.contains("not the last"));
+        assert_eq!((diags[1].lnum, diags[1].severity), (3, Severity::Warning));
+        assert!(diags[1].message.contains("line 1"));
+
+        // Host names are compared as in a lookup: ignoring ASCII case and a
+        // trailing dot.
+        let input = "machine github.com login me password p\n\
+                     machine GitHub.com login me password q\n\
+                     machine github.com. login me password r\n";
+        let diags = lint(Cursor::new(input.as_bytes())).unwrap();
+        assert_eq!(diags.iter().map(|d| d.lnum).collect::<Vec<_>>(), vec![2, 3]);
+        assert!(diags.iter().all(|d| d.message.contains("shadowed by the record on line 1")));
+
+        // Records for different ports do not shadow each other.
+        assert_eq!(lint_str("machine a login u port 80\nmachine a login u port 8080\n"), vec![]);
+    }
+
+    #[test]
+    fn lint_unterminated_quote_column_counts_chars() {
+        assert_eq!(lint_str("machine \u{e9}x login \"abc\n"),
+                   vec![(1, 1, Severity::Warning),
+                        (1, 18, Severity::Error)]);
+    }
+
+    #[test]
+    fn lint_macdef() {
+        let input = "macdef init\ncd /pub\nmachine a login u password p\n";
+        assert_eq!(lint_str(input),
+                   vec![(1, 1, Severity::Warning),
+                        (3, 1, Severity::Warning)]);
+
+        assert_eq!(lint_str("macdef init\ncd /pub\n\nmachine a login u\n"), vec![]);
+    }
+
+    #[test]
+    fn lint_github_without_token() {
+        assert_eq!(lint_str("machine api.github.com login u\n"),
+                   vec![(1, 1, Severity::Warning)]);
+        assert_eq!(lint_str("machine github.com login u password ghp_x\n"), vec![]);
+        assert_eq!(lint_str("machine github.com login u password_cmd \"pass show gh\"\n"), vec![]);
+    }
+
+    #[test]
+    fn lint_password_cmd() {
+        assert_eq!(lint_str("machine a login u password p password_cmd cat\n"),
+                   vec![(1, 1, Severity::Warning)]);
+        assert_eq!(lint_str("machine a login u password_cmd \"pass show 'x\"\n"),
+                   vec![(1, 32, Severity::Error)]);
+    }
+}
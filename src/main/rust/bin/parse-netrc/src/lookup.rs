@@ -0,0 +1,692 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Looking up netrc records, the way **`parse-netrc`** (and curl) does.
+//!
+//! A lookup has two parts: a [`NetrcSource`] says where the netrc data comes
+//! from (a file, a list of files, the user's default netrc file(s), or any
+//! reader), and a [`Query`] says which record is wanted. Loading a source
+//! yields a [`Loaded`] set of parsed files, which can be queried any number
+//! of times; each successful query yields a [`Match`] that records the file
+//! and line number of the record found.
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::lookup::{NetrcSource, Query};
+//!
+//! let netrc = "machine api.github.com login me password s3cret\n\
+//!              default login anonymous\n";
+//! let loaded = NetrcSource::reader("<example>", netrc.as_bytes()).load().unwrap();
+//!
+//! let found = Query::host("api.github.com").find(&loaded).unwrap();
+//! assert_eq!(found.machine.login, "me");
+//! assert_eq!((found.file.to_str(), found.line()), (Some("<example>"), 1));
+//!
+//! // No 'machine' record, so the 'default' record is used...
+//! assert!(Query::host("example.com").find(&loaded).unwrap().is_default());
+//!
+//! // ...unless that fallback is turned off.
+//! assert!(Query::host("example.com").default_fallback(false).find(&loaded).is_err());
+//! ```
+
+use std::env;
+use std::error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::decrypt::{self, Decrypt};
+use crate::netrc::{self, Machine, Netrc};
+use crate::password_cmd;
+use crate::url::{self, Url};
+
+/// The errors that can result from loading a [`NetrcSource`] or running a
+/// [`Query`].
+#[derive(Debug)]
+pub enum Error {
+    /// The user's home directory (for the default `~/.netrc`) is unknown.
+    NoHomeDir,
+
+    /// A netrc file that must exist (the only one in the source) does not.
+    NotFound(PathBuf),
+
+    /// None of the files in a list of netrc files exist.
+    NoneFound(Vec<PathBuf>),
+
+    /// The path names something other than a regular file.
+    NotAFile(PathBuf),
+
+    /// The netrc file could not be read.
+    Io { path: PathBuf, source: io::Error },
+
+    /// The netrc file could not be parsed.
+    Parse { path: PathBuf, source: netrc::Error },
+
+    /// The (encrypted) netrc file could not be decrypted.
+    Decrypt { path: PathBuf, source: password_cmd::Error },
+
+    /// The netrc file is accessible by group or others, or is not owned by
+    /// the effective user. Curl (and ftp(1) before it) may refuse to use such
+    /// a file. The `reason` describes the problem.
+    InsecureFile { path: PathBuf, reason: String },
+
+    /// No record matched the query.
+    NoMatch { host: String, user: Option<String> },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoHomeDir =>
+                write!(f, "unable to determine the user's home directory"),
+            Error::NotFound(path) =>
+                write!(f, "file does not exist: {:?}", path),
+            Error::NoneFound(paths) =>
+                write!(f, "none of the listed netrc files exist: {:?}", paths),
+            Error::NotAFile(path) =>
+                write!(f, "{:?} exists, but is not a file", path),
+            Error::Io { path, source } =>
+                write!(f, "unable to read the netrc file {:?}: {}", path, source),
+            Error::Parse { path, source: netrc::Error::Parse(msg, lnum) } =>
+                write!(f, "unable to parse the netrc file {:?}: line {}: {}", path, lnum, msg),
+            Error::Parse { path, source: netrc::Error::Io(err) } =>
+                write!(f, "unable to read the netrc file {:?}: {}", path, err),
+            Error::Decrypt { path, source } =>
+                write!(f, "unable to decrypt the netrc file {:?}: {}", path, source),
+            Error::InsecureFile { path, reason } =>
+                write!(f, "netrc file {:?} is not safe to use: {}; curl(1) may refuse to use it", path, reason),
+            Error::NoMatch { host, user: None } =>
+                write!(f, "No matching netrc record found for machine: \"{}\"", host),
+            Error::NoMatch { host, user: Some(user) } =>
+                write!(f, "No matching netrc record found for machine: \"{}\" and user: \"{}\"", host, user),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. }      => Some(source),
+            Error::Decrypt { source, .. } => Some(source),
+            Error::Parse { source: netrc::Error::Io(err), .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// What to do about a netrc file with unsafe permissions or ownership (see
+/// [`Error::InsecureFile`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermsCheck {
+    /// Refuse to use the file (the default).
+    Strict,
+
+    /// Use the file, but note the problem in [`Loaded::warnings`].
+    Warn,
+
+    /// Do not check.
+    Off,
+}
+
+enum SourceKind {
+    Paths(Vec<PathBuf>),
+    Reader { name: PathBuf, reader: Box<dyn Read> },
+}
+
+/// Where netrc data comes from. See the module-level docs.
+pub struct NetrcSource {
+    kind: SourceKind,
+    perms_check: PermsCheck,
+    decrypt: Decrypt,
+}
+
+impl NetrcSource {
+    /// A single netrc file, which must exist.
+    pub fn file<P: Into<PathBuf>>(path: P) -> NetrcSource {
+        NetrcSource::paths(vec![path.into()])
+    }
+
+    /// A list of netrc files, searched in order. When there is more than one,
+    /// the files that do not exist are skipped (but at least one must).
+    pub fn paths(paths: Vec<PathBuf>) -> NetrcSource {
+        NetrcSource { kind: SourceKind::Paths(paths), perms_check: PermsCheck::Strict, decrypt: Decrypt::Auto }
+    }
+
+    /// A list of netrc files in the form of a `PATH`-like list (e.g.,
+    /// `"~/.netrc-work:/etc/netrc"` on Unix). Empty elements are ignored.
+    pub fn path_list<S: AsRef<OsStr> + ?Sized>(list: &S) -> NetrcSource {
+        let paths = env::split_paths(list)
+            .filter(|path| !path.as_os_str().is_empty())
+            .collect();
+        NetrcSource::paths(paths)
+    }
+
+    /// The netrc file(s) that curl would read: those named by the `NETRC`
+    /// environment variable (as a `PATH`-like list) if it is set and not
+    /// empty, or else `$HOME/.netrc`.
+    pub fn from_env() -> Result<NetrcSource, Error> {
+        match env::var_os("NETRC") {
+            Some(ref list) if !list.is_empty() => Ok(NetrcSource::path_list(list)),
+            _ => {
+                let home_dir = home::home_dir().ok_or(Error::NoHomeDir)?;
+                Ok(NetrcSource::file(home_dir.join(".netrc")))
+            }
+        }
+    }
+
+    /// Netrc data read from `reader`, rather than from a file. The `name` is
+    /// reported as the file of any [`Match`]. No permissions check is done.
+    pub fn reader<N: Into<PathBuf>, R: Read + 'static>(name: N, reader: R) -> NetrcSource {
+        NetrcSource {
+            kind: SourceKind::Reader { name: name.into(), reader: Box::new(reader) },
+            perms_check: PermsCheck::Off,
+            decrypt: Decrypt::Auto,
+        }
+    }
+
+    /// Sets what to do about files with unsafe permissions or ownership.
+    pub fn perms_check(mut self, perms_check: PermsCheck) -> NetrcSource {
+        self.perms_check = perms_check;
+        self
+    }
+
+    /// Sets which files are decrypted, and how (see the
+    /// [`decrypt`](crate::decrypt) module). By default, `.gpg` and `.asc`
+    /// files are decrypted with gpg. The data of a reader is never decrypted.
+    pub fn decrypt(mut self, decrypt: Decrypt) -> NetrcSource {
+        self.decrypt = decrypt;
+        self
+    }
+
+    /// The netrc files of this source (empty for a reader).
+    pub fn file_paths(&self) -> &[PathBuf] {
+        match self.kind {
+            SourceKind::Paths(ref paths) => paths,
+            SourceKind::Reader { .. }    => &[],
+        }
+    }
+
+    /// Reads and parses the netrc data.
+    pub fn load(self) -> Result<Loaded, Error> {
+        let mut loaded = Loaded { files: Vec::new(), warnings: Vec::new() };
+
+        let paths = match self.kind {
+            SourceKind::Reader { name, reader } => {
+                let mut netrc = Netrc::parse(BufReader::new(reader))
+                    .map_err(|source| Error::Parse { path: name.clone(), source })?;
+                lock_passwords(&mut netrc);
+                loaded.files.push((name, netrc));
+                return Ok(loaded);
+            }
+            SourceKind::Paths(paths) => paths,
+        };
+
+        let have_multiple_files = paths.len() > 1;
+
+        for path in &paths {
+            if !path.exists() {
+                if have_multiple_files {
+                    continue;
+                }
+                return Err(Error::NotFound(path.clone()));
+            }
+            if !path.is_file() {
+                return Err(Error::NotAFile(path.clone()));
+            }
+
+            if PermsCheck::Off != self.perms_check {
+                let problem = file_perms_problem(path)
+                    .map_err(|source| Error::Io { path: path.clone(), source })?;
+                if let Some(reason) = problem {
+                    let err = Error::InsecureFile { path: path.clone(), reason };
+                    if PermsCheck::Strict == self.perms_check {
+                        return Err(err);
+                    }
+                    loaded.warnings.push(err);
+                }
+            }
+
+            let file = File::open(path)
+                .map_err(|source| Error::Io { path: path.clone(), source })?;
+            let netrc = match self.decrypt.command_for(path) {
+                Some(cmd) => {
+                    let plaintext = decrypt::decrypt(cmd, file, decrypt::DEFAULT_TIMEOUT)
+                        .map_err(|source| Error::Decrypt { path: path.clone(), source })?;
+                    Netrc::parse(plaintext.expose_secret())
+                }
+                None => Netrc::parse(BufReader::new(file)),
+            };
+            let mut netrc = netrc.map_err(|source| Error::Parse { path: path.clone(), source })?;
+            lock_passwords(&mut netrc);
+            loaded.files.push((path.clone(), netrc));
+        }
+
+        if loaded.files.is_empty() {
+            return Err(Error::NoneFound(paths));
+        }
+
+        Ok(loaded)
+    }
+}
+
+// Locks the password of each record of 'netrc' into memory (see
+// Secret::mlock()). This is best effort: a password that cannot be locked
+// (e.g., over the RLIMIT_MEMLOCK limit) is still used.
+fn lock_passwords(netrc: &mut Netrc) {
+    let machines = netrc.hosts.iter_mut().map(|(_, machine)| machine).chain(netrc.default.as_mut());
+    for password in machines.filter_map(|machine| machine.password.as_mut()) {
+        let _ = password.mlock();
+    }
+}
+
+/// Checks the permissions and ownership of the netrc file at `path`. Returns
+/// a description of the problem if the file is accessible by group or others,
+/// or if it is not owned by the effective user; None if the file is safe to
+/// use.
+///
+/// The mode check is the same one that ftp(1) has long performed on the
+/// `~/.netrc` file ("mode & 077"). On other than Unix-like systems, no check
+/// is done.
+#[cfg(unix)]
+pub fn file_perms_problem(path: &Path) -> io::Result<Option<String>> {
+    use std::os::unix::fs::MetadataExt;
+
+    // The 'std' library does not expose geteuid(2), and we do not otherwise
+    // need the 'libc' crate. The C library is linked into every Rust program
+    // on Unix-like systems, anyway, and uid_t is a 32-bit unsigned integer on
+    // all of the platforms that we care about.
+    extern "C" {
+        fn geteuid() -> u32;
+    }
+
+    let metadata = fs::metadata(path)?;
+
+    let mode = metadata.mode() & 0o7777;
+    if 0 != (mode & 0o077) {
+        return Ok(Some(format!("mode {:04o} permits access by group or others (try: chmod go-rwx)", mode)));
+    }
+
+    let euid = unsafe { geteuid() };
+    if metadata.uid() != euid {
+        return Ok(Some(format!("owned by uid {}, not by the effective user (uid {})", metadata.uid(), euid)));
+    }
+
+    Ok(None)
+}
+
+#[cfg(not(unix))]
+pub fn file_perms_problem(_path: &Path) -> io::Result<Option<String>> {
+    Ok(None)
+}
+
+/// The parsed netrc files of a [`NetrcSource`], in search order.
+#[derive(Debug)]
+pub struct Loaded {
+    /// Each file (or reader name) with its parsed content.
+    pub files: Vec<(PathBuf, Netrc)>,
+
+    /// The problems that were noted, but tolerated (see [`PermsCheck::Warn`]).
+    pub warnings: Vec<Error>,
+}
+
+/// A netrc record found by a [`Query`].
+#[derive(Debug, Clone, Copy)]
+pub struct Match<'a> {
+    /// The file (or reader name) in which the record was found.
+    pub file: &'a Path,
+
+    /// The name from the record's `machine` entry, or None for the `default`
+    /// record.
+    pub hostname: Option<&'a str>,
+
+    pub machine: &'a Machine,
+}
+
+impl Match<'_> {
+    /// The line number on which the record begins.
+    pub fn line(&self) -> usize {
+        self.machine.lnum
+    }
+
+    /// Whether this is the `default` record.
+    pub fn is_default(&self) -> bool {
+        self.hostname.is_none()
+    }
+}
+
+/// Describes the netrc record wanted from a lookup.
+///
+/// As with curl, all of the `machine` records (in all of the files, in order)
+/// are considered before any `default` record, and the first record that
+/// matches wins. The [`account`](Query::account) and [`index`](Query::index)
+/// selectors have no counterpart in curl, which always uses the first record
+/// for the host (and login, if one is given).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    host: String,
+    user: Option<String>,
+    account: Option<String>,
+    index: Option<usize>,
+    port: Option<u16>,
+    default_fallback: bool,
+    normalize_hosts: bool,
+}
+
+impl Query {
+    /// A query for the record for `host`. By default, host names must match
+    /// exactly but for ASCII case (as DNS names do), any login matches, the
+    /// `port` of a record is ignored, and the `default` record is used if no
+    /// `machine` record matches.
+    pub fn host<S: Into<String>>(host: S) -> Query {
+        Query {
+            host: host.into(),
+            user: None,
+            account: None,
+            index: None,
+            port: None,
+            default_fallback: true,
+            normalize_hosts: false,
+        }
+    }
+
+    /// A query for the record for the host and effective port of `url`,
+    /// comparing host names in their normalized forms. A user name in the
+    /// URL sets the wanted user.
+    pub fn url(url: &Url) -> Query {
+        Query {
+            host: url.host.clone(),
+            user: url.username.clone(),
+            account: None,
+            index: None,
+            port: url.effective_port(),
+            default_fallback: true,
+            normalize_hosts: true,
+        }
+    }
+
+    /// Requires the `login` of the record to be `user`.
+    pub fn user<S: Into<String>>(mut self, user: S) -> Query {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Requires the `account` of the record to be `account`.
+    pub fn account<S: Into<String>>(mut self, account: S) -> Query {
+        self.account = Some(account.into());
+        self
+    }
+
+    /// Selects the `index`th (1-based) of the matching `machine` records, in
+    /// file order (see [`Query::find_all`]), instead of the preferred one. An
+    /// index of zero matches nothing. The `default` record is not counted, and
+    /// is not used as a fallback.
+    pub fn index(mut self, index: usize) -> Query {
+        self.index = Some(index);
+        self
+    }
+
+    /// Prefers the first record whose `port` is `port`; failing that, the
+    /// first record with no `port` is used. Records for other ports are not
+    /// considered.
+    pub fn port(mut self, port: u16) -> Query {
+        self.port = Some(port);
+        self
+    }
+
+    /// Sets whether the `default` record is used when no `machine` record
+    /// matches.
+    pub fn default_fallback(mut self, default_fallback: bool) -> Query {
+        self.default_fallback = default_fallback;
+        self
+    }
+
+    /// Sets whether host names are compared in their normalized forms (see
+    /// [`url::normalize_host`]), rather than as given (ignoring ASCII case).
+    pub fn normalize_hosts(mut self, normalize_hosts: bool) -> Query {
+        self.normalize_hosts = normalize_hosts;
+        self
+    }
+
+    /// The wanted host.
+    pub fn wanted_host(&self) -> &str {
+        &self.host
+    }
+
+    /// The wanted user, if any.
+    pub fn wanted_user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// The wanted account, if any.
+    pub fn wanted_account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    /// The wanted index, if any (see [`Query::index`]).
+    pub fn wanted_index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// The wanted port, if any.
+    pub fn wanted_port(&self) -> Option<u16> {
+        self.port
+    }
+
+    fn user_matches(&self, machine: &Machine) -> bool {
+        self.user.as_ref().map_or(true, |user| *user == machine.login)
+            && self.account.as_ref().map_or(true, |account| Some(account) == machine.account.as_ref())
+    }
+
+    /// Finds the record for this query, including the `default` record
+    /// fallback (if enabled).
+    pub fn find<'a>(&self, loaded: &'a Loaded) -> Result<Match<'a>, Error> {
+        let fallback = self.default_fallback && self.index.is_none();
+        self.find_machine(loaded)
+            .or_else(|| if fallback { self.find_default(loaded) } else { None })
+            .ok_or_else(|| Error::NoMatch { host: self.host.clone(), user: self.user.clone() })
+    }
+
+    /// Finds the `machine` record for this query, if any.
+    pub fn find_machine<'a>(&self, loaded: &'a Loaded) -> Option<Match<'a>> {
+        let candidates = self.find_all(loaded);
+        if let Some(index) = self.index {
+            return index.checked_sub(1).and_then(|idx| candidates.get(idx)).copied();
+        }
+        match self.port {
+            None => candidates.first().copied(),
+            Some(port) => candidates.iter().find(|m| m.machine.port == Some(port))
+                .or_else(|| candidates.first())
+                .copied(),
+        }
+    }
+
+    /// Finds all of the `machine` records that match this query (ignoring
+    /// any [`index`](Query::index)), in file order. With a wanted port, records
+    /// for other ports are left out.
+    pub fn find_all<'a>(&self, loaded: &'a Loaded) -> Vec<Match<'a>> {
+        let wanted_host = if self.normalize_hosts { url::normalize_host(&self.host) } else { self.host.clone() };
+
+        loaded.files.iter()
+            .flat_map(|(file, netrc)| netrc.hosts.iter().map(move |(hostname, machine)| {
+                Match { file, hostname: Some(hostname), machine }
+            }))
+            .filter(|m| {
+                let hostname = m.hostname.unwrap_or("");
+                if self.normalize_hosts {
+                    url::normalize_host(hostname) == wanted_host
+                }
+                else {
+                    hostname.eq_ignore_ascii_case(&wanted_host)
+                }
+            })
+            .filter(|m| self.user_matches(m.machine))
+            .filter(|m| self.port.map_or(true, |port| m.machine.port.map_or(true, |p| p == port)))
+            .collect()
+    }
+
+    /// Finds the first `default` record whose login (and account) matches the
+    /// wanted user (and account), if any, regardless of whether the fallback is
+    /// enabled.
+    pub fn find_default<'a>(&self, loaded: &'a Loaded) -> Option<Match<'a>> {
+        loaded.files.iter()
+            .filter_map(|(file, netrc)| netrc.default.as_ref().map(|machine| Match { file, hostname: None, machine }))
+            .find(|m| self.user_matches(m.machine))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    const NETRC: &str = "\
+machine ghe.corp login a password p1 port 8443
+machine GHE.corp. login b password p2
+machine ghe.corp login c password p3
+machine ghe.corp login d password p4 port 443
+default login anonymous
+";
+
+    fn load(s: &'static str) -> Loaded {
+        NetrcSource::reader("test", s.as_bytes()).load().unwrap()
+    }
+
+    #[test]
+    fn query_host_and_user() {
+        let loaded = load(NETRC);
+        let m = Query::host("ghe.corp").find(&loaded).unwrap();
+        assert_eq!((m.machine.login.as_str(), m.line(), m.file), ("a", 1, Path::new("test")));
+
+        let m = Query::host("ghe.corp").user("c").find(&loaded).unwrap();
+        assert_eq!(m.line(), 3);
+
+        let m = Query::host("ghe.corp").user("anonymous").find(&loaded).unwrap();
+        assert!(m.is_default());
+
+        match Query::host("ghe.corp").user("z").find(&loaded) {
+            Err(Error::NoMatch { host, user }) => assert_eq!((host.as_str(), user.as_deref()), ("ghe.corp", Some("z"))),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn query_host_ignores_ascii_case() {
+        let loaded = load(NETRC);
+        let m = Query::host("GHE.Corp").find(&loaded).unwrap();
+        assert_eq!((m.machine.login.as_str(), m.hostname), ("a", Some("ghe.corp")));
+        assert_eq!(Query::host("GHE.CORP").find_all(&loaded).len(), 3);
+
+        let loaded = load("machine api.github.com login me password p\ndefault login anonymous\n");
+        let m = Query::host("API.GITHUB.COM").find(&loaded).unwrap();
+        assert!(!m.is_default());
+        assert_eq!(m.machine.login, "me");
+    }
+
+    #[test]
+    fn query_port_and_normalized_host() {
+        let loaded = load(NETRC);
+        let m = Query::host("ghe.corp").port(8443).find(&loaded).unwrap();
+        assert_eq!(m.machine.login, "a");
+
+        // Falls back to the first record with no port (which, without host
+        // name normalization, is not the one for "GHE.corp.").
+        let m = Query::host("ghe.corp").port(80).find(&loaded).unwrap();
+        assert_eq!(m.machine.login, "c");
+
+        let url = Url::parse("https://GHE.CORP/api/v3").unwrap();
+        let m = Query::url(&url).find(&loaded).unwrap();
+        assert_eq!(m.machine.login, "d");
+
+        let url = Url::parse("http://ghe.corp/").unwrap();
+        let m = Query::url(&url).find(&loaded).unwrap();
+        assert_eq!(m.machine.login, "b");
+    }
+
+    #[test]
+    fn query_all_account_and_index() {
+        let loaded = load("\
+machine api.github.com login work password w account acme
+machine example.com login other
+machine api.github.com login me password p account personal
+machine api.github.com login bot password b
+default login anonymous account acme
+");
+        let logins = |q: Query| q.find_all(&loaded).iter().map(|m| m.machine.login.clone()).collect::<Vec<_>>();
+        assert_eq!(logins(Query::host("api.github.com")), ["work", "me", "bot"]);
+        assert_eq!(logins(Query::host("api.github.com").account("personal")), ["me"]);
+        assert!(logins(Query::host("nowhere")).is_empty());
+
+        let m = Query::host("api.github.com").account("personal").find(&loaded).unwrap();
+        assert_eq!((m.machine.login.as_str(), m.line()), ("me", 3));
+        let m = Query::host("nowhere").account("acme").find(&loaded).unwrap();
+        assert!(m.is_default());
+
+        let m = Query::host("api.github.com").index(3).find(&loaded).unwrap();
+        assert_eq!(m.machine.login, "bot");
+        let m = Query::host("api.github.com").user("me").index(1).find(&loaded).unwrap();
+        assert_eq!(m.machine.login, "me");
+        // Out of range, and no 'default' fallback when selecting by index.
+        assert!(Query::host("api.github.com").index(4).find(&loaded).is_err());
+        assert!(Query::host("api.github.com").index(0).find(&loaded).is_err());
+        assert!(Query::host("nowhere").index(1).find(&loaded).is_err());
+    }
+
+    #[test]
+    fn load_path_list() {
+        let dir = env::temp_dir().join(format!("parse-netrc-lookup-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let present = dir.join("present");
+        fs::write(&present, "machine h login from-file\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&present, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        let missing = dir.join("missing");
+        let list = env::join_paths(&[&missing, &present]).unwrap();
+
+        #[cfg(unix)]
+        match NetrcSource::path_list(&list).load() {
+            Err(Error::InsecureFile { path, .. }) => assert_eq!(path, present),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let loaded = NetrcSource::path_list(&list).perms_check(PermsCheck::Warn).load().unwrap();
+        assert_eq!(loaded.files.len(), 1);
+        assert_eq!(Query::host("h").find(&loaded).unwrap().file, present.as_path());
+        #[cfg(unix)]
+        assert_eq!(loaded.warnings.len(), 1);
+
+        match NetrcSource::file(&missing).load() {
+            Err(Error::NotFound(path)) => assert_eq!(path, missing),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let err = NetrcSource::reader("bad", "machine h login u
+machine
+".as_bytes()).load().unwrap_err();
+        assert!(format!("{}", err).starts_with("unable to parse the netrc file \"bad\": line 2: "), "{}", err);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,826 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2014 Paul Woolcock </text>
+// SPDX-FileCopyrightText: <text> © 2014 Jimmy Lu <gongchuo.lu@gmail.com> </text>
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: MIT
+//
+//     Permission is hereby granted, free of charge, to any person obtaining a copy
+//     of this software and associated documentation files (the "Software"), to deal
+//     in the Software without restriction, including without limitation the rights
+//     to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+//     copies of the Software, and to permit persons to whom the Software is
+//     furnished to do so, subject to the following conditions:
+//
+//     The above copyright notice and this permission notice shall be included in
+//     all copies or substantial portions of the Software.
+//
+//     THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//     IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//     FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//     AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+//     LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+//     OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+//     THE SOFTWARE.
+
+//! Parses and encapsulates the `netrc` file format.
+//!
+//! This module started life as an in-tree copy of version 0.4.1 of the
+//! third-party [`netrc`][netrc] crate, which we formerly consumed as a
+//! "vendored" dependency. That version of the library cannot correctly parse
+//! all of the netrc files that curl(1) accepts, and the upstream project has
+//! been quiet for some time, so we carry the code here where we can fix it.
+//!
+//! The public interface (`Netrc`, `Machine`, `Error`, etc.) is largely
+//! unchanged from the upstream crate. The original MIT license terms are
+//! retained for this file so that our changes can be offered back upstream.
+//!
+//! Differences from upstream netrc-0.4.1
+//! -------------------------------------
+//!
+//! * Tokens may be enclosed in double quotes, which allows them to contain
+//!   whitespace. Within a quoted token, a backslash escapes the character
+//!   that follows it; `\n`, `\r` and `\t` produce a newline, carriage return
+//!   and tab, respectively. This matches the behavior of curl 7.84.0 and
+//!   newer. Unquoted tokens are taken literally (including any embedded
+//!   quote or backslash characters).
+//!
+//! * A `#` character that begins a token starts a comment that runs to the
+//!   end of the line, whether it appears at the start of the line or after
+//!   other tokens. A `#` in the middle of a token, or within a quoted token,
+//!   is just an ordinary character. As with curl, lines within a `macdef`
+//!   body are not tokenized at all, so a `#` line there is part of the
+//!   macro body (and, not being blank, does not end it).
+//!
+//! * Each `Machine` records (in its `lnum` field) the line number on which
+//!   its `machine` or `default` keyword appeared.
+//!
+//! * A `macdef` body is ended by a blank line with a CRLF terminator, too,
+//!   and not by a one-character last line with no line terminator at all.
+//!   The body (in `Netrc::macros`) is just the lines that follow the
+//!   `macdef` line, as written; it does not include the blank line.
+//!
+//! * The `password` of a `Machine` is a [`Secret`], which is redacted when
+//!   formatted (so `{:?}` on a `Machine` or `Netrc` never shows it) and
+//!   zeroed on drop.
+//!
+//! * The input is read as bytes, not as lines of text, so a file that is not
+//!   valid UTF-8 (e.g., one with a Latin-1 comment) can still be parsed. As
+//!   with curl, tokens are separated by ASCII whitespace only (space, tab,
+//!   CR, LF, VT and FF); a Unicode space character (such as U+00A0) is part
+//!   of a token. Each token must be valid UTF-8, or it is a parse error; a
+//!   `macdef` body that is not is decoded with U+FFFD replacement characters.
+//!   A UTF-8 byte order mark at the start of the input is skipped, and CRLF
+//!   line terminators are accepted anywhere.
+//!
+//! * A `password_cmd` entry (our extension, which curl does not know) gives
+//!   a command that prints the password, in `Machine::password_cmd`. The
+//!   command is not run here (see the [`password_cmd`](crate::password_cmd)
+//!   module).
+//!
+//! [netrc]: https://crates.io/crates/netrc  "netrc (crates.io)"
+
+use std::io::BufRead;
+
+use crate::secret::Secret;
+
+pub type Macro = (String, String);
+pub type Host = (String, Machine);
+
+#[derive(Debug, Default, Clone)]
+pub struct Machine {
+    pub lnum: usize,
+    pub login: String,
+    pub password: Option<Secret>,
+    pub password_cmd: Option<String>,
+    pub account: Option<String>,
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Default)]
+pub struct Netrc {
+    pub hosts: Vec<Host>,
+    pub default: Option<Machine>,
+    pub macros: Vec<Macro>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse(String, usize),
+}
+
+pub type Result<A> = std::result::Result<A, Error>;
+
+impl Netrc {
+    /// Parse a `Netrc` object from byte stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use parse_netrc::netrc::Netrc;
+    /// use std::io::Cursor;
+    ///
+    /// let input: Cursor<&[u8]> =
+    ///   Cursor::new(b"machine example.com login foo password bar");
+    /// let netrc = Netrc::parse(input).unwrap();
+    /// ```
+    pub fn parse<A: BufRead>(buf: A) -> Result<Netrc> {
+        let mut netrc: Netrc = Default::default();
+        let mut lexer = Lexer::new(buf);
+        let mut current_machine = MachineRef::Nothing;
+        loop {
+            match lexer.next_word() {
+                None         => break,
+                Some(Err(e)) => return Err(e),
+                Some(Ok(w))  => {
+                    current_machine = netrc.parse_entry(&mut lexer, &w, current_machine)?;
+                }
+            }
+        }
+        Ok(netrc)
+    }
+
+    fn parse_entry<A: BufRead>(&mut self,
+                               lexer: &mut Lexer<A>,
+                               item: &str,
+                               current_machine: MachineRef) -> Result<MachineRef> {
+        macro_rules! with_current_machine {
+            ($entry: expr, $machine: ident, $body: block) => {
+                match self.find_machine(&current_machine) {
+                    Some($machine) => {
+                        $body;
+                        Ok(current_machine)
+                    }
+                    None =>
+                        Err(Error::Parse(format!("No machine defined for {}",
+                                                 $entry),
+                                         lexer.lnum)),
+                }
+            }
+        }
+
+        match item {
+            "machine" => {
+                let lnum = lexer.lnum;
+                let host_name = lexer.next_word_or_err()?;
+                self.hosts.push((host_name, Machine { lnum, ..Default::default() }));
+                Ok(MachineRef::Host(self.hosts.len() - 1))
+            }
+            "default" => {
+                self.default = Some(Machine { lnum: lexer.lnum, ..Default::default() });
+                Ok(MachineRef::Default)
+            }
+            "login" => with_current_machine!("login", m, {
+                m.login = lexer.next_word_or_err()?;
+            }),
+            "password" => with_current_machine!("password", m, {
+                m.password = Some(Secret::new(lexer.next_word_or_err()?));
+            }),
+            "password_cmd" => with_current_machine!("password_cmd", m, {
+                m.password_cmd = Some(lexer.next_word_or_err()?);
+            }),
+            "account" => with_current_machine!("account", m, {
+                m.account = Some(lexer.next_word_or_err()?);
+            }),
+            "port" => with_current_machine!("port", m, {
+                let port = lexer.next_word_or_err()?;
+                match port.parse() {
+                    Ok(port) => m.port = Some(port),
+                    Err(_)   => {
+                        let msg = format!("Unable to parse port number `{}'",
+                                          port);
+                        return Err(Error::Parse(msg, lexer.lnum));
+                    }
+                }
+            }),
+            "macdef" => {
+                let name = lexer.next_word_or_err()?;
+                let cmds = lexer.next_subcommands()?;
+                self.macros.push((name, cmds));
+                Ok(MachineRef::Nothing)
+            }
+            _ => Err(Error::Parse(format!("Unknown entry `{}'", item),
+                                  lexer.lnum)),
+        }
+    }
+
+    fn find_machine(&mut self,
+                    reference: &MachineRef) -> Option<&mut Machine> {
+        match *reference {
+            MachineRef::Nothing => None,
+            MachineRef::Default => self.default.as_mut(),
+            MachineRef::Host(n) => Some(&mut self.hosts[n].1),
+        }
+    }
+}
+
+enum MachineRef {
+    Nothing,
+    Default,
+    Host(usize),
+}
+
+pub(crate) struct Tokens {
+    buf: Vec<u8>,
+
+    // The byte offset into 'buf' just past the most recently returned token.
+    pub(crate) cur: usize,
+
+    // The byte offset into 'buf' at which the most recently returned (or
+    // rejected) token started.
+    pub(crate) start: usize,
+}
+
+/// Whether `byte` separates tokens. As for curl (which uses the C library's
+/// isspace() in the "C" locale), only ASCII whitespace counts. No byte of a
+/// multi-byte UTF-8 sequence is ASCII, so scanning the bytes of UTF-8 text
+/// for these never splits a character.
+pub(crate) fn is_separator(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c)
+}
+
+impl Tokens {
+    pub(crate) fn new<B: Into<Vec<u8>>>(buf: B) -> Tokens {
+        Tokens { buf: buf.into(), cur: 0, start: 0 }
+    }
+
+    fn empty() -> Tokens {
+        Tokens::new(Vec::new())
+    }
+
+    // Returns the next token on the line, if any. A token that begins with
+    // '#' starts a comment, which consumes the rest of the line. The error
+    // case carries a message describing a malformed token (e.g., a quoted
+    // token with no closing quote, or one that is not valid UTF-8); the
+    // caller is expected to decorate it with the line number. Either way,
+    // 'cur' is left past the token.
+    //
+    // Both 'start' and 'cur' are byte offsets into 'buf'. They fall on char
+    // boundaries whenever the text around them is valid UTF-8.
+    //
+    pub(crate) fn next(&mut self) -> Option<std::result::Result<String, String>> {
+        let start = match self.buf[self.cur..].iter().position(|&b| !is_separator(b)) {
+            Some(idx) => self.cur + idx,
+            None => {
+                self.cur = self.buf.len();
+                return None;
+            }
+        };
+        self.cur = start;
+        self.start = start;
+
+        match self.buf[start] {
+            b'#' => {
+                self.cur = self.buf.len();
+                None
+            }
+            b'"' => Some(self.next_quoted()),
+            _ => {
+                let len = self.buf[start..].iter().position(|&b| is_separator(b))
+                    .unwrap_or(self.buf.len() - start);
+                self.cur = start + len;
+                Some(utf8_token(self.buf[start..start + len].to_vec()))
+            }
+        }
+    }
+
+    // Consumes a double-quoted token, with 'cur' positioned on the opening
+    // quote. A quoted token may not span lines.
+    //
+    fn next_quoted(&mut self) -> std::result::Result<String, String> {
+        let body_start = self.cur + 1;  // skip opening quote
+        let mut tok: Vec<u8> = Vec::new();
+        let mut bytes = self.buf[body_start..].iter().enumerate();
+        while let Some((idx, &b)) = bytes.next() {
+            match b {
+                b'"' => {
+                    self.cur = body_start + idx + 1;  // skip closing quote
+                    return utf8_token(tok);
+                }
+                // The escaped byte may begin a multi-byte character, in
+                // which case the rest of it follows as ordinary bytes.
+                b'\\' => match bytes.next() {
+                    Some((_, b'n')) => tok.push(b'\n'),
+                    Some((_, b'r')) => tok.push(b'\r'),
+                    Some((_, b't')) => tok.push(b'\t'),
+                    Some((_, &other)) => tok.push(other),
+                    None => break,
+                },
+                _ => tok.push(b),
+            }
+        }
+        self.cur = self.buf.len();
+        Err("Unterminated quoted token".to_string())
+    }
+}
+
+fn utf8_token(bytes: Vec<u8>) -> std::result::Result<String, String> {
+    String::from_utf8(bytes)
+        .map_err(|err| format!("Token is not valid UTF-8 (invalid byte at offset {} of the token)",
+                               err.utf8_error().valid_up_to()))
+}
+
+/// Whether `line` (as read, with its line terminator) is the blank line that
+/// ends a `macdef` body. A blank line with a CRLF terminator counts.
+pub(crate) fn is_blank_line<L: AsRef<[u8]>>(line: L) -> bool {
+    matches!(line.as_ref(), b"\n" | b"\r\n")
+}
+
+/// A token read by the `Lexer`, along with its position in the input.
+///
+/// The `lnum` and `col` values are both 1-based; `col` counts characters
+/// (not bytes) from the start of the line.
+///
+#[derive(Debug)]
+pub(crate) struct Token {
+    pub(crate) text: String,
+    pub(crate) lnum: usize,
+    pub(crate) col: usize,
+}
+
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+pub(crate) struct Lexer<A> {
+    buf: A,
+    line: Tokens,
+    pub(crate) lnum: usize,
+}
+
+impl<A: BufRead> Lexer<A> {
+    pub(crate) fn new(buf: A) -> Lexer<A> {
+        Lexer { buf, line: Tokens::empty(), lnum: 0 }
+    }
+
+    /// Returns the (1-based, in characters) column at which the most
+    /// recently returned or rejected token started on the current line. (A
+    /// byte that is not part of a valid UTF-8 sequence counts as a character.)
+    pub(crate) fn col(&self) -> usize {
+        String::from_utf8_lossy(&self.line.buf[..self.line.start]).chars().count() + 1
+    }
+
+    // Reads the next line, with its terminator (if any), as bytes. A UTF-8
+    // byte order mark at the start of the input is dropped.
+    fn read_line(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        let n = self.buf.read_until(b'\n', buf).map_err(Error::Io)?;
+        if n > 0 {
+            self.lnum += 1;
+            if 1 == self.lnum && buf.starts_with(UTF8_BOM) {
+                buf.drain(..UTF8_BOM.len());
+            }
+        }
+        Ok(n)
+    }
+
+    fn refill(&mut self) -> Result<usize> {
+        let mut line = Vec::new();
+        let n = self.read_line(&mut line)?;
+        self.line = Tokens::new(line);
+        Ok(n)
+    }
+
+    /// Like `next_word`, but also reports the position of the token.
+    pub(crate) fn next_token(&mut self) -> Option<Result<Token>> {
+        self.next_word().map(|w| w.map(|text| Token { text, lnum: self.lnum, col: self.col() }))
+    }
+
+    fn next_word(&mut self) -> Option<Result<String>> {
+        loop {
+            match self.line.next() {
+                Some(Ok(w))    => return Some(Ok(w)),
+                Some(Err(msg)) => return Some(Err(Error::Parse(msg, self.lnum))),
+                None           => match self.refill() {
+                    Ok(0)  => return None,
+                    Ok(_)  => (),
+                    Err(e) => return Some(Err(e)),
+                },
+            }
+        }
+    }
+
+    fn next_word_or_err(&mut self) -> Result<String> {
+        match self.next_word() {
+            Some(w) => w,
+            None    => Err(Error::Parse("Unexpected end of file".to_string(),
+                                        self.lnum)),
+        }
+    }
+
+    fn next_subcommands(&mut self) -> Result<String> {
+        self.read_subcommands().map(|(cmds, _)| cmds)
+    }
+
+    /// Reads the body of a `macdef`: the lines that follow the `macdef`
+    /// line, up to the next blank line (or the end of the input). Anything
+    /// after the macro name on the `macdef` line itself is ignored. The flag
+    /// returned with the body is false if the end of the input was reached
+    /// before a blank line was seen.
+    pub(crate) fn read_subcommands(&mut self) -> Result<(String, bool)> {
+        let mut cmds = String::new();
+        self.line = Tokens::empty();
+        loop {
+            let mut line = Vec::new();
+            match self.read_line(&mut line)? {
+                0                         => return Ok((cmds, false)),
+                _ if is_blank_line(&line) => return Ok((cmds, true)),
+                _                         => cmds.push_str(&String::from_utf8_lossy(&line)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn parse_simple() {
+        let input = "machine example.com
+                             login test
+                             password p@ssw0rd
+                             port 42";
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        assert_eq!(netrc.hosts.len(), 1);
+        assert!(netrc.macros.is_empty());
+        let (ref name, ref mach) = netrc.hosts[0];
+        assert_eq!(name, "example.com");
+        assert_eq!(mach.login, "test");
+        assert_eq!(mach.password.as_ref().unwrap(), "p@ssw0rd");
+        assert_eq!(mach.port, Some(42));
+        assert_eq!(mach.account, None);
+        assert_eq!(mach.lnum, 1);
+    }
+
+    #[test]
+    fn parse_password_cmd() {
+        let input = "machine api.github.com login me password_cmd \"pass show 'github/my token'\"\n\
+                     default login anon password_cmd cat";
+        let netrc = Netrc::parse(BufReader::new(input.as_bytes())).unwrap();
+        let (_, ref mach) = netrc.hosts[0];
+        assert_eq!(mach.password_cmd.as_deref(), Some("pass show 'github/my token'"));
+        assert!(mach.password.is_none());
+        assert_eq!(netrc.default.unwrap().password_cmd.as_deref(), Some("cat"));
+    }
+
+    #[test]
+    fn parse_macdef() {
+        let input = "machine host1.com login login1
+                     macdef uploadtest
+                            cd /pub/tests
+                            bin
+                            put filename.tar.gz
+                            quit
+
+                     machine host2.com login login2";
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        assert_eq!(netrc.hosts.len(), 2);
+        for host in netrc.hosts.iter().enumerate() {
+            let (i, (name, mach)) = host;
+            let i = i + 1;
+            assert_eq!(name, &format!("host{}.com", i));
+            assert_eq!(mach.login, format!("login{}", i));
+        }
+        assert_eq!(netrc.macros.len(), 1);
+        let (ref name, ref cmds) = netrc.macros[0];
+        assert_eq!(name, "uploadtest");
+        assert_eq!(cmds.trim(), "cd /pub/tests
+                            bin
+                            put filename.tar.gz
+                            quit");
+    }
+
+    #[test]
+    fn parse_macdef_crlf() {
+        let input = "macdef init\r\ncd /pub\r\n\r\nmachine host1.com login login1\r\n";
+        let netrc = Netrc::parse(BufReader::new(input.as_bytes())).unwrap();
+        assert_eq!(netrc.macros, vec![("init".to_string(), "cd /pub\r\n".to_string())]);
+        assert_eq!(netrc.hosts.len(), 1);
+        assert_eq!(netrc.hosts[0].0, "host1.com");
+    }
+
+    #[test]
+    fn parse_macdef_short_last_line() {
+        let input = "macdef init\ncd /pub\nx";
+        let netrc = Netrc::parse(BufReader::new(input.as_bytes())).unwrap();
+        assert_eq!(netrc.macros, vec![("init".to_string(), "cd /pub\nx".to_string())]);
+    }
+
+    #[test]
+    fn parse_default() {
+        let input = "machine example.com login test
+                     default login def";
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        assert_eq!(netrc.hosts.len(), 1);
+        let (ref name, ref mach) = netrc.hosts[0];
+        assert_eq!(name, "example.com");
+        assert_eq!(mach.login, "test");
+        let def_mach = netrc.default.unwrap();
+        assert_eq!(def_mach.login, "def");
+        assert_eq!(def_mach.lnum, 2);
+    }
+
+    #[test]
+    fn parse_quoted_tokens() {
+        let input = r#"machine api.github.com
+                       login "some user"
+                       password "a b\"c\\d\te""#;
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        let (ref name, ref mach) = netrc.hosts[0];
+        assert_eq!(name, "api.github.com");
+        assert_eq!(mach.login, "some user");
+        assert_eq!(mach.password.as_ref().unwrap(), "a b\"c\\d\te");
+    }
+
+    #[test]
+    fn parse_quoted_empty_token() {
+        let input = r#"machine "example.com" login "" password "p w""#;
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        let (ref name, ref mach) = netrc.hosts[0];
+        assert_eq!(name, "example.com");
+        assert_eq!(mach.login, "");
+        assert_eq!(mach.password.as_ref().unwrap(), "p w");
+    }
+
+    #[test]
+    fn parse_unquoted_tokens_are_literal() {
+        let input = r#"machine example.com login us"er password p\w"#;
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        let (_, ref mach) = netrc.hosts[0];
+        assert_eq!(mach.login, "us\"er");
+        assert_eq!(mach.password.as_ref().unwrap(), "p\\w");
+    }
+
+    #[test]
+    fn parse_non_ascii_tokens() {
+        let input = "machine example.com login j\u{f6}rg password \u{1f511}";
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        let (_, ref mach) = netrc.hosts[0];
+        assert_eq!(mach.login, "j\u{f6}rg");
+        assert_eq!(mach.password.as_ref().unwrap(), "\u{1f511}");
+    }
+
+    #[test]
+    fn parse_comment_at_line_start() {
+        let input = "# work account
+                     machine example.com
+                     # the token below expires in 90 days
+                     login test password p@ssw0rd
+                     #machine commented-out.com login nobody";
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        assert_eq!(netrc.hosts.len(), 1);
+        let (ref name, ref mach) = netrc.hosts[0];
+        assert_eq!(name, "example.com");
+        assert_eq!(mach.login, "test");
+        assert_eq!(mach.password.as_ref().unwrap(), "p@ssw0rd");
+    }
+
+    #[test]
+    fn parse_comment_after_tokens() {
+        let input = "machine example.com login test # work account
+                     password p@ssw0rd #no space needed after the hash
+                     machine other.com login other";
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        assert_eq!(netrc.hosts.len(), 2);
+        let (_, ref mach) = netrc.hosts[0];
+        assert_eq!(mach.login, "test");
+        assert_eq!(mach.password.as_ref().unwrap(), "p@ssw0rd");
+        let (ref name, ref mach) = netrc.hosts[1];
+        assert_eq!(name, "other.com");
+        assert_eq!(mach.login, "other");
+    }
+
+    #[test]
+    fn parse_hash_within_token_is_literal() {
+        let input = r##"machine example.com login te#st password "# not a comment""##;
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        let (_, ref mach) = netrc.hosts[0];
+        assert_eq!(mach.login, "te#st");
+        assert_eq!(mach.password.as_ref().unwrap(), "# not a comment");
+    }
+
+    #[test]
+    fn parse_comment_inside_macdef() {
+        let input = "machine host1.com login login1
+                     macdef uploadtest
+                            # change to the upload dir
+                            cd /pub/tests
+                     # not blank, so the macro continues
+                            quit
+
+                     # back outside of the macro
+                     machine host2.com login login2";
+        let input = BufReader::new(input.as_bytes());
+        let netrc = Netrc::parse(input).unwrap();
+        assert_eq!(netrc.hosts.len(), 2);
+        assert_eq!(netrc.hosts[1].0, "host2.com");
+        assert_eq!(netrc.macros.len(), 1);
+        let (ref name, ref cmds) = netrc.macros[0];
+        assert_eq!(name, "uploadtest");
+        assert_eq!(cmds.trim(), "# change to the upload dir
+                            cd /pub/tests
+                     # not blank, so the macro continues
+                            quit");
+    }
+
+    #[test]
+    fn parse_error_unterminated_quote() {
+        let input = "machine foobar.com
+                             login \"quux
+                             password bar";
+        let input = BufReader::new(input.as_bytes());
+        match Netrc::parse(input).unwrap_err() {
+            Error::Parse(msg, lnum) => {
+                assert_eq!(msg, "Unterminated quoted token");
+                assert_eq!(lnum, 2);
+            }
+            e => panic!("Wrong Error type: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_error_unknown_entry() {
+        let input = "machine foobar.com
+                             foo";
+        let input = BufReader::new(input.as_bytes());
+        match Netrc::parse(input).unwrap_err() {
+            Error::Parse(msg, lnum) => {
+                assert_eq!(msg, "Unknown entry `foo'");
+                assert_eq!(lnum, 2);
+            }
+            e => panic!("Wrong Error type: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_error_unexpected_eof() {
+        let input = "machine foobar.com
+                             password quux
+                             login";
+        let input = BufReader::new(input.as_bytes());
+        match Netrc::parse(input).unwrap_err() {
+            Error::Parse(msg, lnum) => {
+                assert_eq!(msg, "Unexpected end of file");
+                assert_eq!(lnum, 3);
+            }
+            e => panic!("Wrong Error type: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_error_no_machine() {
+        let input = "password quux login foo";
+        let input = BufReader::new(input.as_bytes());
+        match Netrc::parse(input).unwrap_err() {
+            Error::Parse(msg, lnum) => {
+                assert_eq!(msg, "No machine defined for password");
+                assert_eq!(lnum, 1);
+            }
+            e => panic!("Wrong Error type: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_error_port() {
+        let input = "machine foo.com login bar port quux";
+        let input = BufReader::new(input.as_bytes());
+        match Netrc::parse(input).unwrap_err() {
+            Error::Parse(msg, lnum) => {
+                assert_eq!(msg, "Unable to parse port number `quux'");
+                assert_eq!(lnum, 1);
+            }
+            e => panic!("Wrong Error type: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_crlf_and_bom() {
+        let input = b"\xef\xbb\xbfmachine host1.com\r\n  login \"us er\"\r\n  password p1\r\n\r\ndefault login anon\r\n";
+        let netrc = Netrc::parse(&input[..]).unwrap();
+        assert_eq!(netrc.hosts[0].0, "host1.com");
+        assert_eq!(netrc.hosts[0].1.login, "us er");
+        assert_eq!(netrc.hosts[0].1.password.as_ref().unwrap(), "p1");
+        assert_eq!(netrc.default.unwrap().lnum, 5);
+    }
+
+    #[test]
+    fn parse_unicode_space_is_part_of_token() {
+        let input = "machine example.com login a\u{a0}b password \"\\\u{e9}\u{2003}\"";
+        let netrc = Netrc::parse(input.as_bytes()).unwrap();
+        let (_, ref mach) = netrc.hosts[0];
+        assert_eq!(mach.login, "a\u{a0}b");
+        assert_eq!(mach.password.as_ref().unwrap(), "\u{e9}\u{2003}");
+    }
+
+    #[test]
+    fn parse_non_utf8_outside_tokens() {
+        let input = b"# caf\xe9 (Latin-1)\nmachine h login u # \xff\xfe\nmacdef m\ncd \xe9\n\n";
+        let netrc = Netrc::parse(&input[..]).unwrap();
+        assert_eq!(netrc.hosts[0].1.login, "u");
+        assert_eq!(netrc.macros[0].1, "cd \u{fffd}\n");
+    }
+
+    #[test]
+    fn parse_error_non_utf8_token() {
+        let input = b"machine h\nlogin caf\xe9 password p";
+        match Netrc::parse(&input[..]).unwrap_err() {
+            Error::Parse(msg, lnum) => {
+                assert_eq!(msg, "Token is not valid UTF-8 (invalid byte at offset 3 of the token)");
+                assert_eq!(lnum, 2);
+            }
+            e => panic!("Wrong Error type: {:?}", e),
+        }
+    }
+
+    // Inputs that have tripped up netrc parsers (this one included), for the
+    // 'never_panics' test: truncated and invalid UTF-8, stray quotes and
+    // backslashes, CR-only and CRLF line ends, a BOM, NULs, and so on.
+    const CORPUS: &[&[u8]] = &[
+        b"",
+        b"\xef\xbb\xbfmachine h login u password p\r\n",
+        b"\xef\xbb",
+        b"machine h login \"unterminated",
+        b"machine h login \"ends in a backslash\\",
+        b"machine h login \"\\\xc3\xa9\" password \xc3",
+        b"machine \xe2\x80\x83 login \xf0\x9f\x94\x91\xff account \xc0\x80",
+        b"machine h\r\nlogin u\r\nmacdef m\r\ncmd \xfe\r\n\r\nmachine i login v\r\n",
+        b"default\x0bpassword\x0cp\x00q port 65536 port -1 port \xd9\xa3",
+        b"# \xe9t\xe9\nmachine h login u #c\xff\n",
+        b"macdef",
+        b"macdef m\n\n\n\n",
+        b"macdef \xff\nmachine x\n",
+        b"machine h login u\rpassword p\r",
+        b"\"\"\"\" # \"",
+        b"machine h login u\n\xef\xbb\xbfmachine i login v\n",
+        b"machine h login u password_cmd \"sh -c 'echo \\\"x\\\"'\" password p\n",
+    ];
+
+    // A deterministic (xorshift64) stream of pseudo-random numbers, so that a
+    // failure can be reproduced.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    // Every parser in the crate must cope with 'input' without panicking, and
+    // a document that parses must reproduce its input exactly.
+    fn check(input: &[u8]) {
+        let linted = crate::lint::lint(input).unwrap();
+        if let Err(err) = Netrc::parse(input) {
+            assert!(linted.iter().any(|diag| crate::lint::Severity::Error == diag.severity),
+                    "lint found no error, but the parse failed: {:?}", err);
+        }
+        if let Ok(doc) = crate::document::Document::parse(input) {
+            assert_eq!(doc.to_string().as_bytes(), input);
+        }
+    }
+
+    #[test]
+    fn never_panics() {
+        const INTERESTING: &[u8] = b"\"\\# \t\r\n\x0b\x00\xc3\xa9\xe2\x80\xff";
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+        for seed in CORPUS {
+            check(seed);
+        }
+        for _ in 0..5000 {
+            let mut input = CORPUS[rng.below(CORPUS.len())].to_vec();
+            for _ in 0..1 + rng.below(4) {
+                let at = rng.below(input.len() + 1);
+                match rng.below(5) {
+                    0 => input.insert(at, INTERESTING[rng.below(INTERESTING.len())]),
+                    1 => input.insert(at, rng.next() as u8),
+                    2 if at < input.len() => { input.remove(at); },
+                    3 => input.truncate(at),
+                    _ => {
+                        let other = CORPUS[rng.below(CORPUS.len())];
+                        let from = rng.below(other.len() + 1);
+                        input.splice(at..at, other[from..].iter().copied());
+                    }
+                }
+            }
+            let result = std::panic::catch_unwind(|| check(&input));
+            assert!(result.is_ok(), "failed on input: {:?}", String::from_utf8_lossy(&input));
+        }
+    }
+}
@@ -0,0 +1,292 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Runs the command of a `password_cmd` netrc entry (our extension to the
+//! format), whose output is the password of the record. This keeps the
+//! password itself out of the netrc file:
+//!
+//! ```text
+//! machine api.github.com login me password_cmd "pass show github/token"
+//! ```
+//!
+//! The command is split into words much as a POSIX shell would (see
+//! [`split_words`]), but is run directly, not by a shell: there is no
+//! expansion of variables, `~` or globs, and no pipes or redirections (for
+//! those, the command can run `sh -c '...'` itself). Its stdin is
+//! `/dev/null`, its stderr is ours, and it must exit successfully within the
+//! timeout. The password is its stdout, with leading and trailing whitespace
+//! trimmed; that must be valid UTF-8, non-empty, and a single line.
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::password_cmd;
+//!
+//! let words = password_cmd::split_words(r#"pass show "github/my token""#).unwrap();
+//! assert_eq!(words, ["pass", "show", "github/my token"]);
+//! ```
+
+use std::error;
+use std::fmt;
+use std::io::{self, Read};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::secret::{Secret, SecretBytes};
+
+/// How long a command is given to finish, by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// How often a command that has closed its stdout is checked for having
+// exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The ways in which a password command (or a decryption command; see the
+/// [`decrypt`](crate::decrypt) module) can fail. None of them include any of
+/// the command's output.
+#[derive(Debug)]
+pub enum Error {
+    /// The command could not be split into words (the message says why).
+    Syntax(String),
+
+    /// The command could not be run.
+    Spawn { program: String, source: io::Error },
+
+    /// Reading the output of, or waiting for, the command failed.
+    Io(io::Error),
+
+    /// The command did not finish within the timeout, and was killed.
+    Timeout(Duration),
+
+    /// The command exited unsuccessfully.
+    Failed(ExitStatus),
+
+    /// The output (trimmed) was empty, not valid UTF-8, or more than one line.
+    BadOutput(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Syntax(msg) =>
+                write!(f, "unable to parse the command: {}", msg),
+            Error::Spawn { program, source } =>
+                write!(f, "unable to run the command \"{}\": {}", program, source),
+            Error::Io(err) =>
+                write!(f, "error while running the command: {}", err),
+            Error::Timeout(timeout) =>
+                write!(f, "the command did not finish within {} seconds, and was killed", timeout.as_secs()),
+            Error::Failed(status) =>
+                write!(f, "the command failed ({})", status),
+            Error::BadOutput(problem) =>
+                write!(f, "the output of the command {}", problem),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Spawn { source, .. } => Some(source),
+            Error::Io(err)              => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Splits `cmd` into words. Words are separated by unquoted whitespace. As
+/// in a POSIX shell, a backslash outside of quotes escapes the character
+/// that follows it, single quotes preserve everything up to the next single
+/// quote, and within double quotes a backslash escapes only `"` and `\`.
+/// Nothing else is special.
+pub fn split_words(cmd: &str) -> Result<Vec<String>, Error> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;  // None between words
+    let mut chars = cmd.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            _ if c.is_whitespace() => {
+                words.extend(word.take());
+                continue;
+            }
+            '\\' => match chars.next() {
+                Some(escaped) => word.get_or_insert_with(String::new).push(escaped),
+                None => return Err(Error::Syntax("it ends with a backslash".to_string())),
+            },
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(quoted) => word.push(quoted),
+                        None => return Err(Error::Syntax("unterminated single quote".to_string())),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('"') => word.push('"'),
+                            Some('\\') => word.push('\\'),
+                            Some(other) => { word.push('\\'); word.push(other); }
+                            None => return Err(Error::Syntax("unterminated double quote".to_string())),
+                        },
+                        Some(quoted) => word.push(quoted),
+                        None => return Err(Error::Syntax("unterminated double quote".to_string())),
+                    }
+                }
+            }
+            _ => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+
+    if words.is_empty() {
+        return Err(Error::Syntax("the command is empty".to_string()));
+    }
+    Ok(words)
+}
+
+/// Runs `cmd` (see the module-level docs), killing it if it has not
+/// finished within `timeout`, and returns the password it printed.
+pub fn run(cmd: &str, timeout: Duration) -> Result<Secret, Error> {
+    let output = capture(cmd, Stdio::null(), timeout)?;
+    let output = output.into_secret().ok_or(Error::BadOutput("is not valid UTF-8"))?;
+
+    let password = output.expose_secret().trim();
+    if password.is_empty() {
+        return Err(Error::BadOutput("is empty"));
+    }
+    if password.contains(&['\n', '\r'][..]) {
+        return Err(Error::BadOutput("is more than one line (print only the password, e.g., with 'head -n 1')"));
+    }
+    let mut password = Secret::new(password.to_string());
+    let _ = password.mlock();  // best effort (see Secret::mlock())
+    Ok(password)
+}
+
+// Runs 'cmd' as described in the module-level docs, but with 'stdin' as its
+// stdin, and returns its output as is.
+pub(crate) fn capture(cmd: &str, stdin: Stdio, timeout: Duration) -> Result<SecretBytes, Error> {
+    let words = split_words(cmd)?;
+    let deadline = Instant::now() + timeout;
+
+    let mut child = Command::new(&words[0])
+        .args(&words[1..])
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|source| Error::Spawn { program: words[0].clone(), source })?;
+
+    // The output is read on another thread, so that a command that never
+    // closes its stdout cannot outlast the timeout.
+    let mut stdout = child.stdout.take().expect("the stdout of the child is piped");
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let read = stdout.read_to_end(&mut output);
+        let output = SecretBytes::new(output);
+        let _ = sender.send(read.map(|_| output));
+    });
+
+    let kill = |child: &mut std::process::Child| {
+        let _ = child.kill();
+        let _ = child.wait();
+        Error::Timeout(timeout)
+    };
+
+    let output = match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(read) => read.map_err(Error::Io)?,
+        Err(_) => return Err(kill(&mut child)),
+    };
+
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(Error::Io)? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            return Err(kill(&mut child));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    if !status.success() {
+        return Err(Error::Failed(status));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_words_like_a_shell() {
+        let cases: &[(&str, Option<&[&str]>)] = &[
+            ("pass show github/token",        Some(&["pass", "show", "github/token"])),
+            ("  a\tb  ",                      Some(&["a", "b"])),
+            (r#"a "b c" 'd e' f\ g"#,         Some(&["a", "b c", "d e", "f g"])),
+            (r#"x"y"'z' "" ''"#,              Some(&["xyz", "", ""])),
+            (r#""\"\\\$" '\'"#,               Some(&["\"\\\\$", "\\"])),
+            ("echo $HOME ~ *",                Some(&["echo", "$HOME", "~", "*"])),
+            ("",                              None),
+            ("   ",                           None),
+            ("a 'b",                          None),
+            ("a \"b",                         None),
+            ("a\\",                           None),
+        ];
+        for (cmd, expected) in cases {
+            let words = split_words(cmd).ok();
+            let expected = expected.map(|ww| ww.iter().map(|w| w.to_string()).collect::<Vec<_>>());
+            assert_eq!(words, expected, "command: {:?}", cmd);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_commands() {
+        let timeout = Duration::from_secs(10);
+
+        let password = run("printf '  s3cret \\n\\n'", timeout).unwrap();
+        assert_eq!(password.expose_secret(), "s3cret");
+
+        // No shell: the '|' is just an argument to echo.
+        assert_eq!(run("echo a | b", timeout).unwrap().expose_secret(), "a | b");
+        assert_eq!(run("sh -c 'echo a | tr a b'", timeout).unwrap().expose_secret(), "b");
+
+        assert!(matches!(run("sh -c 'echo x; exit 3'", timeout), Err(Error::Failed(_))));
+        assert!(matches!(run("printf ' \\n'", timeout), Err(Error::BadOutput(_))));
+        assert!(matches!(run("printf 'a\\nb\\n'", timeout), Err(Error::BadOutput(_))));
+        assert!(matches!(run("printf '\\377'", timeout), Err(Error::BadOutput(_))));
+        assert!(matches!(run("/nonexistent/command", timeout), Err(Error::Spawn { .. })));
+        assert!(matches!(run("'unterminated", timeout), Err(Error::Syntax(_))));
+
+        // Killed at the timeout, whether or not it has closed its stdout.
+        let started = Instant::now();
+        assert!(matches!(run("sleep 10", Duration::from_millis(200)), Err(Error::Timeout(_))));
+        assert!(matches!(run("sh -c 'exec >&-; sleep 10'", Duration::from_millis(200)), Err(Error::Timeout(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
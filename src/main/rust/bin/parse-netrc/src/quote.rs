@@ -0,0 +1,109 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! Quoting values for the JSON and shell output formats.
+//!
+//! # Examples
+//!
+//! ```
+//! use parse_netrc::quote::{json_quote, shell_quote};
+//!
+//! assert_eq!(json_quote("say \"hi\"\n"), r#""say \"hi\"\n""#);
+//! assert_eq!(shell_quote("it's"), r#"'it'\''s'"#);
+//! ```
+
+/// Quotes `val` as a JSON string value (including the surrounding double
+/// quotes). Control characters are escaped; everything else, including
+/// non-ASCII characters, is written as is.
+pub fn json_quote(val: &str) -> String {
+    let mut rtn = String::with_capacity(val.len() + 2);
+    rtn.push('"');
+    for ch in val.chars() {
+        match ch {
+            '"'  => rtn.push_str("\\\""),
+            '\\' => rtn.push_str("\\\\"),
+            '\n' => rtn.push_str("\\n"),
+            '\r' => rtn.push_str("\\r"),
+            '\t' => rtn.push_str("\\t"),
+            cc if (cc as u32) < 0x20 => rtn.push_str(&format!("\\u{:04x}", cc as u32)),
+            cc => rtn.push(cc),
+        }
+    }
+    rtn.push('"');
+    rtn
+}
+
+/// Quotes `val` for safe use as a single word by a POSIX shell (including in
+/// the context of an `eval`). The value is enclosed in single quotes, and
+/// each embedded single quote is written as: `'\''`
+pub fn shell_quote(val: &str) -> String {
+    let mut rtn = String::with_capacity(val.len() + 2);
+    rtn.push('\'');
+    for ch in val.chars() {
+        if '\'' == ch {
+            rtn.push_str("'\\''");
+        }
+        else {
+            rtn.push(ch);
+        }
+    }
+    rtn.push('\'');
+    rtn
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_quoting() {
+        let cases: &[(&str, &str)] = &[
+            ("",                      r#""""#),
+            ("plain",                 r#""plain""#),
+            ("it's",                  r#""it's""#),
+            ("say \"hi\"",            r#""say \"hi\"""#),
+            ("C:\\dir\\",             r#""C:\\dir\\""#),
+            ("a\tb\nc\rd",            r#""a\tb\nc\rd""#),
+            ("\u{0}\u{8}\u{1b}\u{1f}", r#""\u0000\u0008\u001b\u001f""#),
+            ("\u{7f} \u{20}",         "\"\u{7f}  \""),
+            ("j\u{f6}rg \u{1f511}",   "\"j\u{f6}rg \u{1f511}\""),
+            ("\u{2028}",              "\"\u{2028}\""),
+        ];
+        for (val, expected) in cases {
+            assert_eq!(json_quote(val), *expected, "value: {:?}", val);
+        }
+    }
+
+    #[test]
+    fn shell_quoting() {
+        let cases: &[(&str, &str)] = &[
+            ("",                 "''"),
+            ("plain",            "'plain'"),
+            ("it's",             r#"'it'\''s'"#),
+            ("''",               r#"''\'''\'''"#),
+            ("say \"hi\"",       r#"'say "hi"'"#),
+            ("a\\b $HOME `x`",   r#"'a\b $HOME `x`'"#),
+            ("a\tb\nc\u{1b}",    "'a\tb\nc\u{1b}'"),
+            ("j\u{f6}rg \u{1f511}", "'j\u{f6}rg \u{1f511}'"),
+        ];
+        for (val, expected) in cases {
+            assert_eq!(shell_quote(val), *expected, "value: {:?}", val);
+        }
+    }
+}
@@ -0,0 +1,338 @@
+// -*- rust -*-
+
+// SPDX-FileCopyrightText: <text> © 2026 Alan D. Salewski <ads@salewski.email> </text>
+// SPDX-License-Identifier: GPL-2.0-or-later
+//
+//     This program is free software; you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation; either version 2 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program; if not, write to the Free Software Foundation,
+//     Inc., 51 Franklin St, Fifth Floor, Boston, MA 02110-1301,, USA.
+
+//! A wrapper for secret values (passwords and tokens), so that they do not
+//! end up in log output by accident.
+//!
+//! A [`Secret`] is redacted when formatted with either `{}` or `{:?}`, so a
+//! struct that contains one (such as [`netrc::Machine`](crate::netrc::Machine))
+//! can be printed safely with the derived `Debug` impl. The value itself is
+//! available only by calling [`Secret::expose_secret`], which makes every use
+//! of it easy to find.
+//!
+//! The buffer that holds the value is overwritten with zeros when the
+//! `Secret` is dropped, and can optionally be locked into memory (see
+//! [`Secret::mlock`]) so that it is not written to swap. The passwords of the
+//! records loaded by a [`NetrcSource`](crate::lookup::NetrcSource), and those
+//! printed by a `password_cmd`, are locked (best effort). Note that this
+//! protects only the final buffer: copies made before the value was wrapped
+//! (e.g., while the buffer was being grown during parsing), or made from the
+//! exposed value, are not covered; values derived from a secret (such as the
+//! rendered output) are therefore wrapped in a `Secret` of their own.
+
+use std::fmt;
+use std::io;
+use std::ptr;
+use std::sync::atomic::{self, Ordering};
+
+/// What a [`Secret`] looks like when formatted.
+pub const REDACTED: &str = "******";
+
+/// A secret string value. See the module-level docs.
+///
+/// # Examples
+///
+/// ```
+/// use parse_netrc::secret::Secret;
+///
+/// let token = Secret::new("ghp_s3cret".to_string());
+/// assert_eq!(format!("{} {:?}", token, token), "****** ******");
+/// assert_eq!(token.expose_secret(), "ghp_s3cret");
+/// ```
+#[derive(Default)]
+pub struct Secret {
+    value: String,
+    locked: bool,
+}
+
+impl Secret {
+    /// Wraps `value`. The buffer of `value` is taken over as is (not copied).
+    pub fn new(value: String) -> Secret {
+        Secret { value, locked: false }
+    }
+
+    /// Wraps `bytes`, if they are valid UTF-8. If not, they are overwritten
+    /// with zeros before being freed, and None is returned.
+    pub fn from_utf8(bytes: Vec<u8>) -> Option<Secret> {
+        match String::from_utf8(bytes) {
+            Ok(value) => Some(Secret::new(value)),
+            Err(err) => {
+                wipe_bytes(&mut err.into_bytes());
+                None
+            }
+        }
+    }
+
+    /// The secret value itself.
+    pub fn expose_secret(&self) -> &str {
+        &self.value
+    }
+
+    /// Locks the memory pages that hold the value, so that they are not
+    /// written to swap; they are unlocked again on drop. This is best effort:
+    /// it may fail if the process is over its `RLIMIT_MEMLOCK` limit. Pages
+    /// are not reference counted by the kernel, so unlocking may also unlock
+    /// another `Secret` that shares a page. On other than Unix-like systems,
+    /// this does nothing.
+    pub fn mlock(&mut self) -> io::Result<()> {
+        if self.locked || 0 == self.value.capacity() {
+            return Ok(());
+        }
+        sys::mlock(self.value.as_ptr(), self.value.capacity())?;
+        self.locked = true;
+        Ok(())
+    }
+
+    /// Whether the value has been locked into memory (see [`Secret::mlock`]).
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    // Overwrites the whole buffer (not just the value) with zeros.
+    fn wipe(&mut self) {
+        // SAFETY: Zeros are valid UTF-8, so the String remains valid.
+        wipe_bytes(unsafe { self.value.as_mut_vec() });
+    }
+}
+
+// Overwrites the whole buffer of 'buf' (not just its contents) with zeros.
+fn wipe_bytes(buf: &mut Vec<u8>) {
+    let base = buf.as_mut_ptr();
+    for idx in 0..buf.capacity() {
+        // SAFETY: 'idx' is within the allocation of 'buf'. The volatile
+        // writes (and the fence) keep the compiler from eliding the
+        // otherwise "dead" stores.
+        unsafe { ptr::write_volatile(base.add(idx), 0) };
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.wipe();
+        if self.locked {
+            let _ = sys::munlock(self.value.as_ptr(), self.value.capacity());
+        }
+    }
+}
+
+impl Clone for Secret {
+    // A clone is not locked, even when the original is.
+    fn clone(&self) -> Secret {
+        Secret::new(self.value.clone())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Secret {
+        Secret::new(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Secret {
+        Secret::new(value.to_string())
+    }
+}
+
+/// A secret byte string (e.g., decrypted netrc data, which need not be
+/// UTF-8). Like a [`Secret`], it is redacted when formatted, and zeroed on
+/// drop; it is not locked into memory.
+#[derive(Default)]
+pub struct SecretBytes {
+    value: Vec<u8>,
+}
+
+impl SecretBytes {
+    /// Wraps `value`. The buffer of `value` is taken over as is (not copied).
+    pub fn new(value: Vec<u8>) -> SecretBytes {
+        SecretBytes { value }
+    }
+
+    /// The secret value itself.
+    pub fn expose_secret(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Converts to a [`Secret`], if the value is valid UTF-8 (see
+    /// [`Secret::from_utf8`]).
+    pub fn into_secret(mut self) -> Option<Secret> {
+        Secret::from_utf8(std::mem::take(&mut self.value))
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        wipe_bytes(&mut self.value);
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl PartialEq for Secret {
+    fn eq(&self, other: &Secret) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for Secret {}
+
+impl PartialEq<str> for Secret {
+    fn eq(&self, other: &str) -> bool {
+        self.value == other
+    }
+}
+
+impl PartialEq<&str> for Secret {
+    fn eq(&self, other: &&str) -> bool {
+        self.value == *other
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::io;
+
+    // As for geteuid(2) in the 'lookup' module, we declare these ourselves
+    // rather than depend on the 'libc' crate.
+    extern "C" {
+        #[link_name = "mlock"]
+        fn c_mlock(addr: *const u8, len: usize) -> i32;
+        #[link_name = "munlock"]
+        fn c_munlock(addr: *const u8, len: usize) -> i32;
+    }
+
+    pub fn mlock(addr: *const u8, len: usize) -> io::Result<()> {
+        match unsafe { c_mlock(addr, len) } {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    pub fn munlock(addr: *const u8, len: usize) -> io::Result<()> {
+        match unsafe { c_munlock(addr, len) } {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::io;
+
+    pub fn mlock(_addr: *const u8, _len: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub fn munlock(_addr: *const u8, _len: usize) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::document::Document;
+    use crate::netrc::Netrc;
+    use std::io::Cursor;
+
+    const TOKEN: &str = "ghp_0123456789abcdefTOKEN";
+
+    fn assert_redacted(formatted: &str) {
+        assert!(!formatted.contains(TOKEN), "token leaked: {}", formatted);
+        // Nor any recognizable piece of it.
+        for piece in TOKEN.as_bytes().windows(4) {
+            let piece = std::str::from_utf8(piece).unwrap();
+            assert!(!formatted.contains(piece), "token bytes {:?} leaked: {}", piece, formatted);
+        }
+    }
+
+    #[test]
+    fn formatting_redacts() {
+        let secret = Secret::new(TOKEN.to_string());
+        for formatted in &[format!("{}", secret), format!("{:?}", secret), format!("{:#?}", secret),
+                           format!("{:>40}", secret), format!("{:?}", Some(secret.clone()))] {
+            assert_redacted(formatted);
+            assert!(formatted.contains(REDACTED));
+        }
+        assert_eq!(secret.expose_secret(), TOKEN);
+        assert_eq!(secret, TOKEN);
+    }
+
+    #[test]
+    fn from_utf8_checks() {
+        assert_eq!(Secret::from_utf8(TOKEN.as_bytes().to_vec()).unwrap(), TOKEN);
+        assert!(Secret::from_utf8(b"caf\xe9".to_vec()).is_none());
+
+        let bytes = SecretBytes::new(b"caf\xe9".to_vec());
+        assert_eq!(format!("{:?}", bytes), REDACTED);
+        assert_eq!(bytes.expose_secret(), b"caf\xe9");
+        assert!(bytes.into_secret().is_none());
+        assert_eq!(SecretBytes::new(TOKEN.into()).into_secret().unwrap(), TOKEN);
+    }
+
+    #[test]
+    fn netrc_debug_redacts() {
+        let input = format!("machine api.github.com login me password {}\n\
+                             default login anonymous password \"{}\"\n", TOKEN, TOKEN);
+        let netrc = Netrc::parse(Cursor::new(input.as_bytes())).unwrap();
+        assert_redacted(&format!("{:?}", netrc));
+        assert_redacted(&format!("{:#?}", netrc));
+        assert_eq!(netrc.hosts[0].1.password.as_ref().unwrap(), TOKEN);
+    }
+
+    #[test]
+    fn document_debug_redacts() {
+        let input = format!("machine api.github.com login me password {}\n\
+                             machine example.com login password password \"{}\"\n\
+                             default login anonymous password \"{}\"\n", TOKEN, TOKEN, TOKEN);
+        let doc = Document::parse(Cursor::new(input.as_bytes())).unwrap();
+        assert_redacted(&format!("{:?}", doc));
+        assert_redacted(&format!("{:#?}", doc));
+        assert!(format!("{:?}", doc).contains("\"anonymous\""));
+        assert_eq!(doc.to_string(), input);
+    }
+
+    #[test]
+    fn drop_zeroes_buffer() {
+        let mut secret = Secret::new(TOKEN.to_string());
+        let _ = secret.mlock();  // may fail (RLIMIT_MEMLOCK); the zeroing does not depend on it
+        secret.wipe();
+        let bytes = secret.value.as_bytes();
+        assert_eq!(bytes.len(), TOKEN.len());
+        assert!(bytes.iter().all(|&b| 0 == b));
+    }
+}